@@ -0,0 +1,112 @@
+use syn::{Attribute, LitInt, LitStr, Type};
+
+/// Attributes of the structure or enum in `#[shader(...)]`.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// Enum is represented as tag and payload.
+    pub tagged: bool,
+
+    /// Structure of `bool` fields is represented as bit mask.
+    pub bitflags: bool,
+
+    /// Alignment ZST in `shader_data` the size of the structure is rounded up to.
+    pub stride_align: Option<&'static str>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = ContainerAttrs::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("shader") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tagged") {
+                    result.tagged = true;
+                    Ok(())
+                } else if meta.path.is_ident("bitflags") {
+                    result.bitflags = true;
+                    Ok(())
+                } else if meta.path.is_ident("stride_align") {
+                    let align: LitInt = meta.value()?.parse()?;
+                    result.stride_align = Some(align_zst(&align)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown container attribute"))
+                }
+            })?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Alignment ZST in `shader_data` for the alignment in bytes.
+fn align_zst(align: &LitInt) -> syn::Result<&'static str> {
+    match align.base10_parse::<u32>()? {
+        1 => Ok("Align1"),
+        2 => Ok("Align2"),
+        4 => Ok("Align4"),
+        8 => Ok("Align8"),
+        16 => Ok("Align16"),
+        32 => Ok("Align32"),
+        64 => Ok("Align64"),
+        128 => Ok("Align128"),
+        256 => Ok("Align256"),
+        _ => Err(syn::Error::new(
+            align.span(),
+            "`stride_align` must be a power of two up to 256",
+        )),
+    }
+}
+
+/// Attributes of the field in `#[shader(...)]`.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Name of the field in the shader.
+    pub rename: Option<String>,
+
+    /// Field is not sent to the shader.
+    pub skip: bool,
+
+    /// Type the field is converted into before layout.
+    pub as_ty: Option<Type>,
+
+    /// Fields of the nested structure are inlined.
+    pub flatten: bool,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = FieldAttrs::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("shader") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    result.rename = Some(name.value());
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    result.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    result.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("as") {
+                    result.as_ty = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown field attribute"))
+                }
+            })?;
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,147 @@
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, DataStruct, DeriveInput, Type};
+
+use crate::{
+    enums::screaming_snake_case,
+    field::{collect_fields, reject_flatten},
+    lang::{languages, placed},
+};
+
+/// Checks whether type is `bool`.
+fn is_bool(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && path.path.is_ident("bool"),
+        Type::Paren(paren) => is_bool(&paren.elem),
+        Type::Group(group) => is_bool(&group.elem),
+        _ => false,
+    }
+}
+
+/// Derives `ShaderRepr` for structure with `#[shader(bitflags)]` attribute.
+///
+/// Such structures are represented by `u32` mask
+/// where each `bool` field occupies one bit in order of declaration.
+pub fn derive_bitflags(input: &DeriveInput, data: &DataStruct) -> syn::Result<TokenStream2> {
+    if let Some(param) = input.generics.params.first() {
+        return Err(syn::Error::new(
+            param.span(),
+            "`ShaderRepr` cannot be derived for generic bitflags",
+        ));
+    }
+
+    let fields = collect_fields(&data.fields, &[])?;
+    reject_flatten(&fields, "bitflags")?;
+
+    for field in &fields {
+        if let Some(as_ty) = &field.as_ty {
+            return Err(syn::Error::new(
+                as_ty.span(),
+                "`as` attribute is not supported for bitflags",
+            ));
+        }
+
+        if !is_bool(&field.ty) {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "fields of bitflags must be `bool`",
+            ));
+        }
+    }
+
+    if fields.len() > 32 {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "bitflags cannot have more than 32 fields",
+        ));
+    }
+
+    let ident = &input.ident;
+    let members = fields.iter().map(|field| &field.member).collect::<Vec<_>>();
+    let bits = (0..fields.len())
+        .map(|bit| Literal::u32_unsuffixed(bit as u32))
+        .collect::<Vec<_>>();
+
+    let mut tokens = TokenStream2::new();
+
+    for lang in languages() {
+        let layouts = lang.layouts;
+        tokens.extend(lang.marker_impl(input, &[]));
+        tokens.extend(lang.element_marker_impl(input));
+        let lang = format_ident!("{}", lang.ident);
+
+        for layout in layouts {
+            let layout = format_ident!("{}", layout);
+            let shader_repr =
+                quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+
+            tokens.extend(quote! {
+                impl #shader_repr for #ident {
+                    type Align = <u32 as #shader_repr>::Align;
+                    type Repr = u32;
+
+                    #[inline(always)]
+                    fn repr(&self) -> u32 {
+                        0u32 #(| ((self.#members as u32) << #bits))*
+                    }
+                }
+            });
+
+            if let Some(placed) = placed(&layout.to_string()) {
+                tokens.extend(placed.size_impl(ident, &layout, &shader_repr));
+            }
+        }
+
+        if cfg!(feature = "codegen") {
+            let name = ident.to_string();
+            let prefix = screaming_snake_case(&name);
+            let constants = fields
+                .iter()
+                .map(|field| format!("{}_{}", prefix, screaming_snake_case(&field.name)));
+            let has_flag = format!("{}_has_flag", name);
+
+            let codegen = quote!(::shader_data::CodeGen<::shader_data::#lang>);
+            let shader_lang = quote!(<::shader_data::#lang as ::shader_data::ShaderLang>);
+
+            tokens.extend(quote! {
+                impl #codegen for #ident {
+                    #[inline(always)]
+                    fn name() -> ::std::borrow::Cow<'static, str> {
+                        <u32 as #codegen>::name()
+                    }
+
+                    fn definition() -> ::std::option::Option<::std::string::String> {
+                        let uint = <u32 as #codegen>::name();
+                        let mut definition = ::std::string::String::new();
+                        #(
+                            definition.push_str(&#shader_lang::constant_definition(
+                                #constants,
+                                &uint,
+                                &#shader_lang::uint_literal(1u32 << #bits),
+                            ));
+                        )*
+                        definition.push_str(&#shader_lang::function_definition(
+                            #has_flag,
+                            &[("flags", &uint), ("flag", &uint)],
+                            "bool",
+                            &::std::format!("(flags & flag) != {}", #shader_lang::uint_literal(0)),
+                        ));
+                        ::std::option::Option::Some(definition)
+                    }
+                }
+
+                impl ::shader_data::ShaderLoad<::shader_data::#lang> for #ident {
+                    #[inline(always)]
+                    fn load(
+                        word: &dyn ::core::ops::Fn(usize) -> ::std::string::String,
+                        offset: usize,
+                    ) -> ::std::string::String {
+                        <u32 as ::shader_data::ShaderLoad<::shader_data::#lang>>::load(word, offset)
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(tokens)
+}
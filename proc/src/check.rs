@@ -0,0 +1,99 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{spanned::Spanned, Type};
+
+use crate::lang::{describe, Lang};
+
+/// Checks whether the field type is supported by the language and layout.
+///
+/// Only types that are known to be unsupported are detected here,
+/// so that user gets targeted error instead of unsatisfied trait bounds.
+/// Returns span of the unsupported type and error message that names supported alternatives.
+pub fn unsupported(ty: &Type, lang: &Lang, layout: &str) -> Option<(Span, String)> {
+    match ty {
+        Type::Reference(_) | Type::Ptr(_) => Some((
+            ty.span(),
+            format!(
+                "references and pointers are not supported in {}; store the value itself",
+                describe(lang, layout)
+            ),
+        )),
+        Type::Paren(ty) => unsupported(&ty.elem, lang, layout),
+        Type::Group(ty) => unsupported(&ty.elem, lang, layout),
+        Type::Array(ty) => unsupported(&ty.elem, lang, layout),
+        _ => unsupported_ident(ty.to_token_stream(), lang, layout),
+    }
+}
+
+fn unsupported_ident(tokens: TokenStream, lang: &Lang, layout: &str) -> Option<(Span, String)> {
+    tokens.into_iter().find_map(|tt| match tt {
+        TokenTree::Ident(ident) => {
+            let span = ident.span();
+            let ident = ident.to_string();
+            let alternatives = match (&*ident, lang.ident) {
+                ("usize" | "isize" | "u128" | "i128", _) => "use `u32` or `i32` instead",
+                (ident, "Hlsl") if matches!(sized_int(ident), Some("u8")) => {
+                    "HLSL has no 8-bit integers, use `u32` instead"
+                }
+                (ident, "Hlsl") if matches!(sized_int(ident), Some("i8")) => {
+                    "HLSL has no 8-bit integers, use `i32` instead"
+                }
+                (ident, "Wgsl") if matches!(sized_int(ident), Some("u8" | "u16" | "u64")) => {
+                    "WGSL has only 32-bit integers, use `u32` instead"
+                }
+                (ident, "Wgsl") if matches!(sized_int(ident), Some("i8" | "i16" | "i64")) => {
+                    "WGSL has only 32-bit integers, use `i32` instead"
+                }
+                ("char", _) => "use `u32` instead",
+                ("f64", "Wgsl") => "WGSL has no 64-bit floats, use `f32` instead",
+                ("f64", "Msl") => "MSL has no 64-bit floats, use `f32` instead",
+                (ident, "Msl") if is_non_float_matrix(ident) => {
+                    "MSL has only floating-point matrices, use arrays of vectors instead"
+                }
+                (
+                    "bool" | "vec2b" | "vec3b" | "vec4b" | "mat2b" | "mat3b" | "mat4b" | "mat2x2b"
+                    | "mat2x3b" | "mat2x4b" | "mat3x2b" | "mat3x3b" | "mat3x4b" | "mat4x2b"
+                    | "mat4x3b" | "mat4x4b",
+                    "Wgsl",
+                ) => "`bool` is not host-shareable, use `u32` or `#[shader(as = u32)]` instead",
+                _ => return None,
+            };
+            Some((
+                span,
+                format!(
+                    "`{}` is not supported in {}; {}",
+                    ident,
+                    describe(lang, layout),
+                    alternatives
+                ),
+            ))
+        }
+        TokenTree::Group(group) => unsupported_ident(group.stream(), lang, layout),
+        _ => None,
+    })
+}
+
+/// Checks whether identifier is an alias of boolean or integer matrix, like `mat3x4b` or `mat2u`.
+fn is_non_float_matrix(ident: &str) -> bool {
+    let Some(dims) = ident
+        .strip_prefix("mat")
+        .and_then(|rest| rest.strip_suffix(['b', 'i', 'u']))
+    else {
+        return false;
+    };
+
+    matches!(
+        dims,
+        "2" | "3" | "4" | "2x2" | "2x3" | "2x4" | "3x2" | "3x3" | "3x4" | "4x2" | "4x3" | "4x4"
+    )
+}
+
+/// Returns non-32-bit integer scalar of the identifier, like `u8` for `u8` or `vec4u8`.
+fn sized_int(ident: &str) -> Option<&str> {
+    let scalar = ident
+        .strip_prefix("vec")
+        .and_then(|rest| rest.strip_prefix(['2', '3', '4']))
+        .unwrap_or(ident);
+
+    matches!(scalar, "u8" | "i8" | "u16" | "i16" | "u64" | "i64").then_some(scalar)
+}
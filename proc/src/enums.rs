@@ -0,0 +1,230 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, DeriveInput, Fields};
+
+use crate::lang::{languages, placed};
+
+/// Checks whether enum has signed integer representation.
+/// Representations wider than 32 bits are rejected.
+fn is_signed_repr(input: &DeriveInput) -> syn::Result<bool> {
+    let mut signed = false;
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if let Some(ident) = meta.path.get_ident() {
+                    match &*ident.to_string() {
+                        "i8" | "i16" | "i32" => signed = true,
+                        "i64" | "i128" | "isize" | "u64" | "u128" | "usize" => {
+                            return Err(meta.error(format!(
+                                "`#[repr({ident})]` is wider than 32 bits; \
+                                use `#[repr(u32)]` or `#[repr(i32)]` instead",
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(signed)
+}
+
+/// Evaluates discriminant expression if it is an integer literal, possibly negated.
+fn literal_discriminant(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => literal_discriminant(expr).map(|value| -value),
+        syn::Expr::Group(group) => literal_discriminant(&group.expr),
+        syn::Expr::Paren(paren) => literal_discriminant(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Checks that discriminants fit into the shader scalar.
+///
+/// Discriminants that are not integer literals can't be evaluated here,
+/// so compile-time assertions are returned for them instead.
+fn check_discriminants(
+    ident: &syn::Ident,
+    data: &syn::DataEnum,
+    signed: bool,
+) -> syn::Result<TokenStream2> {
+    let (min, max) = if signed {
+        (i32::MIN as i128, i32::MAX as i128)
+    } else {
+        (0, u32::MAX as i128)
+    };
+    let scalar = if signed { "i32" } else { "u32" };
+    let message = |variant: &syn::Ident, negative: bool| {
+        let mut message = format!("discriminant of `{variant}` does not fit into `{scalar}`");
+        if negative && !signed {
+            message.push_str("; use `#[repr(i32)]` for negative discriminants");
+        }
+        message
+    };
+
+    let mut asserts = TokenStream2::new();
+    let mut next = Some(0i128);
+    for variant in &data.variants {
+        let value = match &variant.discriminant {
+            Some((_, expr)) => literal_discriminant(expr),
+            None => next,
+        };
+        let variant_ident = &variant.ident;
+
+        match value {
+            Some(value) if value < min || value > max => {
+                let span = match &variant.discriminant {
+                    Some((_, expr)) => expr.span(),
+                    None => variant.span(),
+                };
+                return Err(syn::Error::new(span, message(variant_ident, value < 0)));
+            }
+            Some(_) => {}
+            None => {
+                let message = message(variant_ident, false);
+                asserts.extend(quote! {
+                    const _: () = ::core::assert!(
+                        #ident::#variant_ident as i128 >= #min
+                            && #ident::#variant_ident as i128 <= #max,
+                        #message,
+                    );
+                });
+            }
+        }
+        next = value.map(|value| value + 1);
+    }
+    Ok(asserts)
+}
+
+/// Converts `CamelCase` name into `SCREAMING_SNAKE_CASE`.
+pub fn screaming_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut result = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_lower = chars.get(idx + 1).is_some_and(|c| c.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_uppercase());
+    }
+    result
+}
+
+/// Derives `ShaderRepr` for enum with only unit variants.
+/// Such enums are represented by integer discriminant.
+pub fn derive_enum(input: &DeriveInput, data: &syn::DataEnum) -> syn::Result<TokenStream2> {
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`ShaderRepr` can be derived only for enums with unit variants, \
+                use `#[shader(tagged)]` for enums with data",
+            ));
+        }
+    }
+
+    let signed = is_signed_repr(input)?;
+    let scalar = if signed { quote!(i32) } else { quote!(u32) };
+
+    let ident = &input.ident;
+    let mut tokens = check_discriminants(ident, data, signed)?;
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+
+    for lang in languages() {
+        let layouts = lang.layouts;
+        tokens.extend(lang.marker_impl(input, &[]));
+        tokens.extend(lang.element_marker_impl(input));
+        let lang = format_ident!("{}", lang.ident);
+
+        for layout in layouts {
+            let layout = format_ident!("{}", layout);
+            let shader_repr =
+                quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+
+            tokens.extend(quote! {
+                impl #shader_repr for #ident {
+                    type Align = <#scalar as #shader_repr>::Align;
+                    type Repr = #scalar;
+
+                    #[inline(always)]
+                    fn repr(&self) -> #scalar {
+                        match self {
+                            #(Self::#variants => Self::#variants as #scalar,)*
+                        }
+                    }
+                }
+            });
+
+            if let Some(placed) = placed(&layout.to_string()) {
+                tokens.extend(placed.size_impl(ident, &layout, &shader_repr));
+            }
+        }
+
+        if cfg!(feature = "codegen") {
+            let prefix = screaming_snake_case(&ident.to_string());
+            let constants = variants.iter().map(|variant| {
+                format!("{}_{}", prefix, screaming_snake_case(&variant.to_string()))
+            });
+
+            let literal = if signed {
+                quote!(int_literal)
+            } else {
+                quote!(uint_literal)
+            };
+
+            tokens.extend(quote! {
+                impl ::shader_data::CodeGen<::shader_data::#lang> for #ident {
+                    #[inline(always)]
+                    fn name() -> ::std::borrow::Cow<'static, str> {
+                        <#scalar as ::shader_data::CodeGen<::shader_data::#lang>>::name()
+                    }
+
+                    fn definition() -> ::std::option::Option<::std::string::String> {
+                        let name = <Self as ::shader_data::CodeGen<::shader_data::#lang>>::name();
+                        let mut definition = ::std::string::String::new();
+                        #(
+                            definition.push_str(
+                                &<::shader_data::#lang as ::shader_data::ShaderLang>::constant_definition(
+                                    #constants,
+                                    &name,
+                                    &<::shader_data::#lang as ::shader_data::ShaderLang>::#literal(
+                                        Self::#variants as #scalar,
+                                    ),
+                                ),
+                            );
+                        )*
+                        ::std::option::Option::Some(definition)
+                    }
+                }
+
+                impl ::shader_data::ShaderLoad<::shader_data::#lang> for #ident {
+                    #[inline(always)]
+                    fn load(
+                        word: &dyn ::core::ops::Fn(usize) -> ::std::string::String,
+                        offset: usize,
+                    ) -> ::std::string::String {
+                        <#scalar as ::shader_data::ShaderLoad<::shader_data::#lang>>::load(word, offset)
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(tokens)
+}
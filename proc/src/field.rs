@@ -0,0 +1,151 @@
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{spanned::Spanned, Fields, Ident, Type};
+
+use crate::{attr::FieldAttrs, check, lang::languages};
+
+/// Field of the structure that goes into the shader.
+pub struct Field {
+    /// Expression to access the field from `self`.
+    pub member: syn::Member,
+
+    /// Identifier of the field in the repr-type.
+    pub ident: Ident,
+
+    /// Name of the field in the shader.
+    pub name: String,
+
+    /// Type of the field.
+    pub ty: Type,
+
+    /// Type the field is converted into before layout.
+    pub as_ty: Option<Type>,
+
+    /// Type of the field depends on generic parameters.
+    pub generic: bool,
+
+    /// Fields of the nested structure are inlined.
+    pub flatten: bool,
+}
+
+impl Field {
+    /// Type which layout is used for the field.
+    pub fn repr_ty(&self) -> &Type {
+        self.as_ty.as_ref().unwrap_or(&self.ty)
+    }
+
+    /// Expression that produces reference to the value used for the field layout.
+    ///
+    /// `access` is expression that produces reference to the field.
+    pub fn value(&self, access: &TokenStream2) -> TokenStream2 {
+        match &self.as_ty {
+            None => access.clone(),
+            Some(as_ty) => {
+                let ty = &self.ty;
+                quote!(&<#as_ty as ::core::convert::From<#ty>>::from(
+                    ::core::clone::Clone::clone(#access),
+                ))
+            }
+        }
+    }
+}
+
+/// Checks whether tokens mention any of the identifiers.
+fn mentions(tokens: TokenStream2, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// Collects fields that are not skipped.
+pub fn collect_fields(fields: &Fields, params: &[Ident]) -> syn::Result<Vec<Field>> {
+    let mut result = Vec::new();
+
+    for (idx, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+
+        let (member, ident) = match &field.ident {
+            Some(ident) => (syn::Member::Named(ident.clone()), ident.clone()),
+            None => (
+                syn::Member::Unnamed(syn::Index::from(idx)),
+                format_ident!("_{}", idx),
+            ),
+        };
+
+        if attrs.flatten && (attrs.rename.is_some() || attrs.as_ty.is_some()) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`flatten` cannot be combined with `rename` or `as`",
+            ));
+        }
+
+        let name = match attrs.rename {
+            Some(name) => name,
+            None => {
+                let name = ident.to_string();
+                name.strip_prefix("r#").unwrap_or(&name).to_owned()
+            }
+        };
+
+        let generic = mentions(field.ty.to_token_stream(), params)
+            || mentions(attrs.as_ty.to_token_stream(), params);
+
+        result.push(Field {
+            member,
+            ident,
+            name,
+            ty: field.ty.clone(),
+            as_ty: attrs.as_ty,
+            generic,
+            flatten: attrs.flatten,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Reports fields with `flatten` attribute in types that do not support it.
+pub fn reject_flatten(fields: &[Field], what: &str) -> syn::Result<()> {
+    match fields.iter().find(|field| field.flatten) {
+        None => Ok(()),
+        Some(field) => Err(syn::Error::new(
+            field.ty.span(),
+            format!("`flatten` is not supported in {what}"),
+        )),
+    }
+}
+
+/// Reports fields with types that are not supported by enabled languages.
+///
+/// Each field is reported once for the first language and layout
+/// that does not support it.
+pub fn check_fields(fields: &[Field]) -> syn::Result<()> {
+    let mut errors = None::<syn::Error>;
+
+    for field in fields {
+        let ty = field.repr_ty();
+        let unsupported = languages().find_map(|lang| {
+            lang.layouts
+                .iter()
+                .find_map(|layout| check::unsupported(ty, lang, layout))
+        });
+
+        if let Some((span, message)) = unsupported {
+            let error = syn::Error::new(span, message);
+            match &mut errors {
+                None => errors = Some(error),
+                Some(errors) => errors.combine(error),
+            }
+        }
+    }
+
+    match errors {
+        None => Ok(()),
+        Some(errors) => Err(errors),
+    }
+}
@@ -0,0 +1,235 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_quote, DeriveInput, Ident, Type, WhereClause};
+
+/// Shader language supported by the derive.
+pub struct Lang {
+    /// Name of the language type in `shader_data`.
+    pub ident: &'static str,
+
+    /// Name of the language used in diagnostics.
+    pub name: &'static str,
+
+    /// Layouts supported by the language.
+    ///
+    /// The first one is the default layout of the language.
+    pub layouts: &'static [&'static str],
+
+    /// Marker trait in `shader_data` for types that can be stored in buffers of the language.
+    pub marker: Option<&'static str>,
+
+    /// Marker trait in `shader_data` for types that can be elements of arrays
+    /// in every layout of the language.
+    ///
+    /// Derived types implement it, as enums are scalars
+    /// and structures are aligned as array elements by the layouts.
+    pub element_marker: Option<&'static str>,
+
+    /// Language is enabled with feature flag.
+    enabled: bool,
+}
+
+const LANGUAGES: &[Lang] = &[
+    Lang {
+        ident: "Glsl",
+        name: "GLSL",
+        layouts: &[
+            "Std140",
+            "Std430",
+            "Scalar",
+            "Std140RowMajor",
+            "Std430RowMajor",
+            "ScalarRowMajor",
+        ],
+        marker: None,
+        element_marker: None,
+        enabled: cfg!(feature = "glsl"),
+    },
+    Lang {
+        ident: "Hlsl",
+        name: "HLSL",
+        layouts: &[
+            "HlslStructured",
+            "HlslCBuffer",
+            "HlslStructuredRowMajor",
+            "HlslCBufferRowMajor",
+        ],
+        marker: None,
+        element_marker: None,
+        enabled: cfg!(feature = "hlsl"),
+    },
+    Lang {
+        ident: "Msl",
+        name: "MSL",
+        layouts: &["DefaultLayout"],
+        marker: None,
+        element_marker: None,
+        enabled: cfg!(feature = "msl"),
+    },
+    Lang {
+        ident: "Wgsl",
+        name: "WGSL",
+        layouts: &["WgslStorage", "WgslUniform"],
+        marker: Some("HostShareable"),
+        element_marker: Some("UniformElement"),
+        enabled: cfg!(feature = "wgsl"),
+    },
+];
+
+impl Lang {
+    /// Implements marker trait of the language for the type, if the language has one.
+    ///
+    /// The type implements the marker only if every type in `bounds` does.
+    pub fn marker_impl(&self, input: &DeriveInput, bounds: &[Type]) -> TokenStream2 {
+        let Some(marker) = self.marker else {
+            return TokenStream2::new();
+        };
+
+        let ident = &input.ident;
+        let marker = format_ident!("{}", marker);
+        let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+        let mut where_clause = input
+            .generics
+            .where_clause
+            .clone()
+            .unwrap_or_else(|| -> WhereClause { parse_quote!(where) });
+        for ty in bounds {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::shader_data::#marker));
+        }
+
+        quote! {
+            impl #impl_generics ::shader_data::#marker for #ident #ty_generics #where_clause {}
+        }
+    }
+
+    /// Implements marker trait of the language for array elements, if the language has one.
+    pub fn element_marker_impl(&self, input: &DeriveInput) -> TokenStream2 {
+        let Some(element_marker) = self.element_marker else {
+            return TokenStream2::new();
+        };
+
+        let ident = &input.ident;
+        let element_marker = format_ident!("{}", element_marker);
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        quote! {
+            impl #impl_generics ::shader_data::#element_marker for #ident #ty_generics #where_clause {}
+        }
+    }
+
+    /// Bound of the generic fields in derived `ShaderRepr` impls.
+    ///
+    /// Marker trait of the language implies its `ShaderRepr` impls, so it is used if there is one.
+    /// Instantiations with types that cannot be stored in buffers
+    /// are then reported only with the diagnostic of the marker.
+    pub fn repr_bound(&self, layout: &str) -> TokenStream2 {
+        match self.marker {
+            None => {
+                let lang = format_ident!("{}", self.ident);
+                let layout = format_ident!("{}", layout);
+                quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>)
+            }
+            Some(marker) => {
+                let marker = format_ident!("{}", marker);
+                quote!(::shader_data::#marker)
+            }
+        }
+    }
+}
+
+/// Shader languages enabled with feature flags.
+pub fn languages() -> impl Iterator<Item = &'static Lang> {
+    LANGUAGES.iter().filter(|lang| lang.enabled)
+}
+
+/// Checks whether structures in the layout are placed by layout rules
+/// that cannot be expressed with Rust alignment.
+///
+/// Such layouts also implement trait with the size of the value in the layout.
+pub fn placed(layout: &str) -> Option<Placed> {
+    match layout {
+        "HlslCBuffer" | "HlslCBufferRowMajor" => Some(Placed {
+            place: "HlslCBuffer",
+            size: "HlslCBufferRepr",
+            struct_align: "Align16",
+        }),
+        _ => None,
+    }
+}
+
+/// Alignment ZST in `shader_data` for structures in the layout,
+/// if the layout requires structures to be aligned more than their fields.
+///
+/// Size of such structures is rounded up to the alignment.
+pub fn struct_align(layout: &str) -> Option<&'static str> {
+    match layout {
+        "Std140" | "Std140RowMajor" | "WgslUniform" => Some("Align16"),
+        _ => None,
+    }
+}
+
+/// Layout where structures are placed by layout rules.
+pub struct Placed {
+    /// Type in `shader_data` with `place` function that computes offsets of the fields.
+    pub place: &'static str,
+
+    /// Trait in `shader_data` with `SIZE` of the value in the layout.
+    ///
+    /// It has the layout as generic parameter.
+    pub size: &'static str,
+
+    /// Alignment ZST in `shader_data` for the structures.
+    pub struct_align: &'static str,
+}
+
+impl Placed {
+    /// Implements trait with the size of the value in the layout
+    /// equal to the size of its repr-type.
+    pub fn size_impl(
+        &self,
+        ident: &Ident,
+        layout: &Ident,
+        shader_repr: &TokenStream2,
+    ) -> TokenStream2 {
+        let size = format_ident!("{}", self.size);
+        quote! {
+            impl ::shader_data::#size<::shader_data::#layout> for #ident {
+                const SIZE: usize = ::core::mem::size_of::<<#ident as #shader_repr>::Repr>();
+            }
+        }
+    }
+
+    /// Implements trait with the size of the structure in the layout.
+    ///
+    /// Members that follow the structure are aligned as the structure.
+    pub fn struct_size_impl(
+        &self,
+        input: &DeriveInput,
+        where_clause: &WhereClause,
+        layout: &Ident,
+        size: &TokenStream2,
+    ) -> TokenStream2 {
+        let ident = &input.ident;
+        let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+        let size_trait = format_ident!("{}", self.size);
+        let struct_align = format_ident!("{}", self.struct_align);
+        quote! {
+            impl #impl_generics ::shader_data::#size_trait<::shader_data::#layout>
+                for #ident #ty_generics #where_clause
+            {
+                const SIZE: usize = #size;
+                const END_ALIGN: usize = ::core::mem::align_of::<::shader_data::#struct_align>();
+            }
+        }
+    }
+}
+
+/// Describes layout of the language for diagnostics.
+pub fn describe(lang: &Lang, layout: &str) -> String {
+    match layout {
+        "DefaultLayout" => format!("{} with default layout", lang.name),
+        _ => format!("{} with `{}` layout", lang.name, layout),
+    }
+}
@@ -1,6 +1,124 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{parse_quote, spanned::Spanned, Data, DeriveInput, GenericParam, Ident, WhereClause};
 
-#[proc_macro_derive(ShaderRepr)]
+use self::{
+    attr::ContainerAttrs,
+    field::{check_fields, collect_fields, Field},
+};
+
+mod attr;
+mod bitflags;
+mod check;
+mod enums;
+mod field;
+mod lang;
+mod structs;
+mod tagged;
+
+/// Identifiers of type and const parameters.
+fn generic_params(input: &DeriveInput) -> Vec<Ident> {
+    input
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.clone()),
+            GenericParam::Const(param) => Some(param.ident.clone()),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect()
+}
+
+/// Identifiers of type parameters.
+pub(crate) fn type_params(input: &DeriveInput) -> Vec<&Ident> {
+    input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect()
+}
+
+/// Where clause of the derived impls.
+///
+/// Every type parameter is bound to be `'static`
+/// and every field type that depends on generic parameters is bound with `bound`.
+pub(crate) fn where_clause(
+    input: &DeriveInput,
+    fields: &[Field],
+    bound: &TokenStream2,
+) -> WhereClause {
+    let mut where_clause = input
+        .generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| parse_quote!(where));
+
+    for param in type_params(input) {
+        where_clause.predicates.push(parse_quote!(#param: 'static));
+    }
+
+    for field in fields.iter().filter(|field| field.generic) {
+        let ty = field.repr_ty();
+        where_clause.predicates.push(parse_quote!(#ty: #bound));
+    }
+
+    where_clause
+}
+
+#[proc_macro_derive(ShaderRepr, attributes(shader))]
 pub fn derive_shader_repr(item: TokenStream) -> TokenStream {
-    item
+    let input = syn::parse_macro_input!(item as DeriveInput);
+
+    match derive(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if let Some(param) = input.generics.lifetimes().next() {
+        return Err(syn::Error::new(
+            param.span(),
+            "`ShaderRepr` cannot be derived for types with lifetime parameters",
+        ));
+    }
+
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+
+    match &input.data {
+        Data::Struct(_) if attrs.tagged => Err(syn::Error::new(
+            input.ident.span(),
+            "`#[shader(tagged)]` can be used only on enums",
+        )),
+        Data::Enum(_) if attrs.bitflags => Err(syn::Error::new(
+            input.ident.span(),
+            "`#[shader(bitflags)]` can be used only on structures",
+        )),
+        Data::Enum(_) if attrs.stride_align.is_some() => Err(syn::Error::new(
+            input.ident.span(),
+            "`#[shader(stride_align)]` can be used only on structures",
+        )),
+        Data::Struct(_) if attrs.bitflags && attrs.stride_align.is_some() => Err(syn::Error::new(
+            input.ident.span(),
+            "`#[shader(stride_align)]` cannot be used with `#[shader(bitflags)]`",
+        )),
+        Data::Struct(data) if attrs.bitflags => bitflags::derive_bitflags(input, data),
+        Data::Struct(data) => {
+            let fields = collect_fields(&data.fields, &generic_params(input))?;
+            check_fields(&fields)?;
+            structs::derive_struct(input, &fields, attrs.stride_align)
+        }
+        Data::Enum(data) if data.variants.is_empty() => Err(syn::Error::new(
+            input.ident.span(),
+            "`ShaderRepr` cannot be derived for enums without variants, \
+            as they have no values to store",
+        )),
+        Data::Enum(data) if attrs.tagged => tagged::derive_tagged(input, data),
+        Data::Enum(data) => enums::derive_enum(input, data),
+        Data::Union(_) => Err(syn::Error::new(
+            input.ident.span(),
+            "`ShaderRepr` cannot be derived for unions",
+        )),
+    }
 }
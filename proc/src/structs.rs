@@ -0,0 +1,859 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, spanned::Spanned, DeriveInput, GenericParam, Generics, Ident, Visibility,
+    WhereClause,
+};
+
+use crate::{
+    field::Field,
+    lang::{describe, languages, placed, struct_align, Lang, Placed},
+    type_params, where_clause,
+};
+
+pub fn derive_struct(
+    input: &DeriveInput,
+    fields: &[Field],
+    stride_align: Option<&str>,
+) -> syn::Result<TokenStream2> {
+    let flatten = fields.iter().any(|field| field.flatten);
+    if flatten {
+        if let Some(param) = input.generics.params.first() {
+            return Err(syn::Error::new(
+                param.span(),
+                "`flatten` is not supported in generic structures",
+            ));
+        }
+    }
+
+    let mut tokens = derive_offset_of(input);
+
+    for lang in languages() {
+        for layout in lang.layouts {
+            let placed = placed(layout);
+
+            // Repr-types of generic structures cannot be arrays of bytes sized by their fields,
+            // so they are laid out with Rust alignment in placed layouts too.
+            if flatten || (placed.is_some() && input.generics.params.is_empty()) {
+                tokens.extend(derive_placed_struct_repr(
+                    input,
+                    fields,
+                    lang,
+                    layout,
+                    placed,
+                    stride_align,
+                ));
+            } else {
+                tokens.extend(derive_struct_repr(
+                    input,
+                    fields,
+                    lang,
+                    layout,
+                    placed,
+                    stride_align,
+                ));
+            }
+        }
+
+        let field_types = fields
+            .iter()
+            .map(|field| field.repr_ty().clone())
+            .collect::<Vec<_>>();
+        tokens.extend(lang.marker_impl(input, &field_types));
+        tokens.extend(lang.element_marker_impl(input));
+
+        let lang = format_ident!("{}", lang.ident);
+
+        if cfg!(feature = "codegen") {
+            tokens.extend(derive_struct_codegen(input, fields, &lang));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Alignment of the repr-type is the largest alignment of its fields.
+pub fn repr_align(fields: &[Field], shader_repr: &TokenStream2) -> TokenStream2 {
+    fields.iter().rev().fold(quote!(()), |align, field| {
+        let ty = field.repr_ty();
+        quote!((<#ty as #shader_repr>::Align, #align))
+    })
+}
+
+/// Name of the alignment field placed before the field in the repr-type.
+fn align_ident(field: &Field) -> Ident {
+    format_ident!("__align_{}", field.ident)
+}
+
+/// Definition of the repr-type with given fields.
+///
+/// Each field is preceded by zero-sized field that aligns it as required by the layout.
+/// Alignment is not wrapped together with the value,
+/// so the next field can be placed in the padding at the end of the value,
+/// like a scalar after `vec3`.
+pub fn repr_struct(
+    vis: &Visibility,
+    repr: &Ident,
+    generics: &Generics,
+    where_clause: &WhereClause,
+    fields: &[Field],
+    shader_repr: &TokenStream2,
+    marker: &TokenStream2,
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let align_idents = fields.iter().map(align_ident);
+    let field_idents = fields.iter().map(|field| &field.ident);
+    let field_types = fields.iter().map(Field::repr_ty).collect::<Vec<_>>();
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #[repr(C)]
+        #vis struct #repr #generics #where_clause {
+            #(
+                #align_idents: <#field_types as #shader_repr>::Align,
+                #field_idents: <#field_types as #shader_repr>::Repr,
+            )*
+            #marker
+        }
+
+        impl #impl_generics ::core::clone::Clone for #repr #ty_generics #where_clause {
+            #[inline(always)]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #repr #ty_generics #where_clause {}
+    }
+}
+
+/// Expression that creates value of the repr-type.
+///
+/// `accesses` are expressions that produce references to the field values.
+pub fn repr_value(
+    repr: &Ident,
+    fields: &[Field],
+    accesses: &[TokenStream2],
+    shader_repr: &TokenStream2,
+    marker: &TokenStream2,
+) -> TokenStream2 {
+    let align_idents = fields.iter().map(align_ident);
+    let field_idents = fields.iter().map(|field| &field.ident);
+    let field_types = fields.iter().map(Field::repr_ty);
+    let field_values = fields
+        .iter()
+        .zip(accesses)
+        .map(|(field, access)| field.value(access));
+
+    quote! {
+        #repr {
+            #(
+                #align_idents: ::core::default::Default::default(),
+                #field_idents: <#field_types as #shader_repr>::repr(#field_values),
+            )*
+            #marker
+        }
+    }
+}
+
+/// Implements `offset_of` method for the structure.
+fn derive_offset_of(input: &DeriveInput) -> TokenStream2 {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns offset of the field in the shader language and layout.
+            ///
+            /// Fails to compile when evaluated in const context with unknown field name.
+            #[allow(dead_code)]
+            pub const fn offset_of<__Lang, __Layout>(field: &str) -> usize
+            where
+                Self: ::shader_data::ShaderFields<__Lang, __Layout>,
+            {
+                ::shader_data::FieldLayout::offset_of(
+                    <Self as ::shader_data::ShaderFields<__Lang, __Layout>>::FIELDS,
+                    field,
+                )
+            }
+        }
+    }
+}
+
+/// Generates repr-type for the structure and implements `ShaderRepr` and `ShaderFields` for it.
+///
+/// For non-generic structures layout of the repr-type is verified at compile time.
+/// Generic structures in placed layouts are verified when their fields are used.
+fn derive_struct_repr(
+    input: &DeriveInput,
+    fields: &[Field],
+    lang: &Lang,
+    layout: &str,
+    placed: Option<Placed>,
+    stride_align: Option<&str>,
+) -> TokenStream2 {
+    let ident = &input.ident;
+    let description = describe(lang, layout);
+    let repr_bound = lang.repr_bound(layout);
+    let lang = format_ident!("{}", lang.ident);
+    let layout = format_ident!("{}", layout);
+    let repr = format_ident!("__{}{}{}Repr", ident, lang, layout);
+
+    let shader_repr =
+        quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let size_trait = placed
+        .as_ref()
+        .map(|placed| format_ident!("{}", placed.size));
+    let where_clause = match &size_trait {
+        None => where_clause(input, fields, &repr_bound),
+        Some(size_trait) => where_clause(
+            input,
+            fields,
+            &quote!(::shader_data::#size_trait<::shader_data::#layout>),
+        ),
+    };
+
+    let field_idents = fields.iter().map(|field| &field.ident);
+    let field_names = fields.iter().map(|field| &field.name);
+    let field_sizes = fields.iter().map(|field| {
+        let ty = field.repr_ty();
+        match &size_trait {
+            None => quote!(::core::mem::size_of::<<#ty as #shader_repr>::Repr>()),
+            Some(size_trait) => {
+                quote!(<#ty as ::shader_data::#size_trait<::shader_data::#layout>>::SIZE)
+            }
+        }
+    });
+    let accesses = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            quote!(&self.#member)
+        })
+        .collect::<Vec<_>>();
+
+    let shader_fields =
+        quote!(::shader_data::ShaderFields<::shader_data::#lang, ::shader_data::#layout>);
+
+    let fields_align = repr_align(fields, &shader_repr);
+
+    // Type parameters may be used only by skipped fields.
+    let type_params = type_params(input);
+    let (mut marker_field, mut marker_value) = if type_params.is_empty() {
+        (quote!(), quote!())
+    } else {
+        (
+            quote!(__marker: ::core::marker::PhantomData<fn() -> (#(#type_params,)*)>,),
+            quote!(__marker: ::core::marker::PhantomData,),
+        )
+    };
+
+    let struct_align = match &placed {
+        None => struct_align(&layout.to_string()),
+        Some(placed) => Some(placed.struct_align),
+    };
+    let mut align = match struct_align {
+        None => fields_align.clone(),
+        Some(struct_align) => {
+            let struct_align = format_ident!("{}", struct_align);
+            marker_field.extend(quote!(__align: ::shader_data::#struct_align,));
+            marker_value.extend(quote!(__align: ::shader_data::#struct_align,));
+            quote!((::shader_data::#struct_align, #fields_align))
+        }
+    };
+
+    if let Some(stride_align) = stride_align {
+        let stride_align = format_ident!("{}", stride_align);
+        marker_field.extend(quote!(__stride_align: ::shader_data::#stride_align,));
+        marker_value.extend(quote!(__stride_align: ::shader_data::#stride_align,));
+        align = quote!((::shader_data::#stride_align, #align));
+    }
+
+    let shader_flatten =
+        quote!(::shader_data::ShaderFlatten<::shader_data::#lang, ::shader_data::#layout>);
+
+    let misplaced = format!("fields of `{ident}` are misplaced in {description}");
+    let assertions = if input.generics.params.is_empty() {
+        let aligned = format!("fields of `{ident}` are misaligned in {description}");
+        let size = format!("size of `{ident}` does not match layout rules of {description}");
+        let struct_size = struct_align.map(|struct_align| {
+            let struct_align = format_ident!("{}", struct_align);
+            quote! {
+                ::core::assert!(
+                    size.is_multiple_of(::core::mem::align_of::<::shader_data::#struct_align>()),
+                    #size,
+                );
+            }
+        });
+
+        quote! {
+            const _: () = {
+                let fields = <#ident as #shader_fields>::FIELDS;
+                let aligns = <#ident as #shader_flatten>::ALIGNS;
+                let size = ::core::mem::size_of::<#repr>();
+                let align = ::core::mem::align_of::<<#ident as #shader_repr>::Align>();
+                ::core::assert!(::shader_data::FieldLayout::are_aligned(fields, aligns), #aligned);
+                ::core::assert!(::shader_data::FieldLayout::are_placed(fields, aligns), #misplaced);
+                ::core::assert!(size == ::shader_data::FieldLayout::struct_size(fields, align), #size);
+                #struct_size
+            };
+        }
+    } else {
+        quote!()
+    };
+
+    let repr_struct = repr_struct(
+        &input.vis,
+        &repr,
+        &input.generics,
+        &where_clause,
+        fields,
+        &shader_repr,
+        &marker_field,
+    );
+    let repr_value = repr_value(&repr, fields, &accesses, &shader_repr, &marker_value);
+
+    let field_aligns = fields.iter().map(|field| field_align(field, &shader_repr));
+    let write_types = fields.iter().map(Field::repr_ty);
+    let write_values = fields
+        .iter()
+        .zip(&accesses)
+        .map(|(field, access)| field.value(access));
+    let write_indices = 0..fields.len();
+
+    let (end_aligns, check_fields, check_repr, size_impl) = match &placed {
+        None => {
+            let field_count = fields.len();
+            (quote!(&[1; #field_count]), quote!(), quote!(), quote!())
+        }
+        Some(placed) => {
+            let size_trait = format_ident!("{}", placed.size);
+            let place = format_ident!("{}", placed.place);
+            let end_aligns = fields.iter().map(|field| {
+                let ty = field.repr_ty();
+                quote!(<#ty as ::shader_data::#size_trait<::shader_data::#layout>>::END_ALIGN)
+            });
+            let struct_size = match stride_align {
+                None => quote!(1),
+                Some(stride_align) => {
+                    let stride_align = format_ident!("{}", stride_align);
+                    quote!(::core::mem::align_of::<::shader_data::#stride_align>())
+                }
+            };
+            let size = quote! {
+                ::shader_data::FieldLayout::struct_size(
+                    <Self as #shader_fields>::FIELDS,
+                    #struct_size,
+                )
+            };
+            (
+                quote!(&[#(#end_aligns,)*]),
+                // Placement by Rust alignment is checked when fields are used,
+                // as generic structures cannot be checked in advance.
+                quote! {
+                    ::core::assert!(
+                        ::shader_data::#place::are_placed(
+                            fields,
+                            <Self as #shader_flatten>::ALIGNS,
+                            <Self as #shader_flatten>::END_ALIGNS,
+                        ),
+                        #misplaced,
+                    );
+                },
+                quote! {
+                    let _ = <Self as #shader_fields>::FIELDS;
+                },
+                placed.struct_size_impl(input, &where_clause, &layout, &size),
+            )
+        }
+    };
+
+    quote! {
+        #repr_struct
+
+        impl #impl_generics #shader_repr for #ident #ty_generics #where_clause {
+            type Align = #align;
+            type Repr = #repr #ty_generics;
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                #check_repr
+                #repr_value
+            }
+        }
+
+        impl #impl_generics #shader_fields for #ident #ty_generics #where_clause {
+            const FIELDS: &'static [::shader_data::FieldLayout] = {
+                let fields: &'static [::shader_data::FieldLayout] = &[
+                    #(
+                        ::shader_data::FieldLayout {
+                            name: #field_names,
+                            offset: ::core::mem::offset_of!(#repr #ty_generics, #field_idents),
+                            size: #field_sizes,
+                        },
+                    )*
+                ];
+                #check_fields
+                fields
+            };
+        }
+
+        #size_impl
+
+        impl #impl_generics #shader_flatten for #ident #ty_generics #where_clause {
+            type Align = #fields_align;
+
+            const ALIGNS: &'static [usize] = &[#(#field_aligns,)*];
+
+            const END_ALIGNS: &'static [usize] = #end_aligns;
+
+            #[inline(always)]
+            #[allow(unused_variables)]
+            fn write_fields(
+                &self,
+                bytes: &mut [::core::mem::MaybeUninit<u8>],
+                fields: &[::shader_data::FieldLayout],
+            ) {
+                #(
+                    fields[#write_indices].write(
+                        bytes,
+                        <#write_types as #shader_repr>::repr(#write_values),
+                    );
+                )*
+            }
+        }
+
+        #assertions
+    }
+}
+
+/// Alignment of the field when it is placed into structure.
+fn field_align(field: &Field, shader_repr: &TokenStream2) -> TokenStream2 {
+    let ty = field.repr_ty();
+    quote! {
+        ::core::mem::align_of::<::shader_data::Aligned<
+            <#ty as #shader_repr>::Align,
+            <#ty as #shader_repr>::Repr,
+        >>()
+    }
+}
+
+/// Generates repr-type for the structure with flattened fields or in the placed layout
+/// and implements `ShaderRepr`, `ShaderFields` and `ShaderFlatten` for it.
+///
+/// Fields of flattened structures are placed individually
+/// and placed layouts have rules that cannot be expressed with Rust alignment,
+/// so the repr-type is an array of bytes with offsets computed at compile time.
+fn derive_placed_struct_repr(
+    input: &DeriveInput,
+    fields: &[Field],
+    lang: &Lang,
+    layout: &str,
+    placed: Option<Placed>,
+    stride_align: Option<&str>,
+) -> TokenStream2 {
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let description = describe(lang, layout);
+    let lang = format_ident!("{}", lang.ident);
+    let layout = format_ident!("{}", layout);
+    let repr = format_ident!("__{}{}{}Repr", ident, lang, layout);
+
+    let shader_repr =
+        quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+    let shader_fields =
+        quote!(::shader_data::ShaderFields<::shader_data::#lang, ::shader_data::#layout>);
+    let shader_flatten =
+        quote!(::shader_data::ShaderFlatten<::shader_data::#lang, ::shader_data::#layout>);
+
+    // Index of the first field layout of each field.
+    let mut start = quote!(0);
+    let mut aligns = Vec::new();
+    let mut end_aligns = Vec::new();
+    let mut layouts = Vec::new();
+    let mut writes = Vec::new();
+    let mut align = quote!(());
+
+    for field in fields.iter().rev() {
+        let ty = field.repr_ty();
+        if field.flatten {
+            align = quote!((<#ty as #shader_flatten>::Align, #align));
+        } else {
+            align = quote!((<#ty as #shader_repr>::Align, #align));
+        }
+    }
+
+    let stride_align = stride_align.map(|stride_align| format_ident!("{}", stride_align));
+
+    let aligned = format!("fields of `{ident}` are misaligned in {description}");
+    let ordered = format!("fields of `{ident}` overlap in {description}");
+    let size = format!("size of `{ident}` does not match layout rules of {description}");
+    let packed = format!("fields of `{ident}` cross register boundary in {description}");
+
+    let (repr_align, place, field_size, struct_size, size_impl, layout_assertions) = match &placed {
+        None => {
+            let (mut repr_align, layout_assertions) = match struct_align(&layout.to_string()) {
+                None => (align.clone(), quote!()),
+                Some(struct_align) => {
+                    let struct_align = format_ident!("{}", struct_align);
+                    (
+                        quote!((::shader_data::#struct_align, #align)),
+                        quote! {
+                            ::core::assert!(
+                                #repr::SIZE.is_multiple_of(::core::mem::align_of::<::shader_data::#struct_align>()),
+                                #size,
+                            );
+                        },
+                    )
+                }
+            };
+            if let Some(stride_align) = &stride_align {
+                repr_align = quote!((::shader_data::#stride_align, #repr_align));
+            }
+            (
+                repr_align.clone(),
+                quote!(::shader_data::FieldLayout::place(fields, &#repr::ALIGNS)),
+                None,
+                quote!(::core::mem::align_of::<#repr_align>()),
+                quote!(),
+                layout_assertions,
+            )
+        }
+        Some(placed) => {
+            let place = format_ident!("{}", placed.place);
+            let size = format_ident!("{}", placed.size);
+            let struct_align = format_ident!("{}", placed.struct_align);
+            let (repr_align, struct_size) = match &stride_align {
+                None => (quote!((::shader_data::#struct_align, #align)), quote!(1)),
+                Some(stride_align) => (
+                    quote!((::shader_data::#stride_align, (::shader_data::#struct_align, #align))),
+                    quote!(::core::mem::align_of::<::shader_data::#stride_align>()),
+                ),
+            };
+            (
+                repr_align,
+                quote!(::shader_data::#place::place(fields, &#repr::ALIGNS, &#repr::END_ALIGNS)),
+                Some(size.clone()),
+                // Size of the structure is not rounded up in placed layouts,
+                // unless it is aligned for dynamic offsets.
+                struct_size,
+                placed.struct_size_impl(input, &parse_quote!(where), &layout, &quote!(#repr::SIZE)),
+                quote! {
+                    ::core::assert!(::shader_data::#place::are_packed(&#repr::FIELDS), #packed);
+                },
+            )
+        }
+    };
+
+    for field in fields {
+        let ty = field.repr_ty();
+        let member = &field.member;
+
+        if field.flatten {
+            let count = quote!(<#ty as #shader_fields>::FIELDS.len());
+            aligns.push(quote! {
+                let inner = <#ty as #shader_flatten>::ALIGNS;
+                let mut idx = 0;
+                while idx < inner.len() {
+                    aligns[#start + idx] = inner[idx];
+                    idx += 1;
+                }
+            });
+            end_aligns.push(quote! {
+                let inner = <#ty as #shader_flatten>::END_ALIGNS;
+                let mut idx = 0;
+                while idx < inner.len() {
+                    end_aligns[#start + idx] = inner[idx];
+                    idx += 1;
+                }
+            });
+            layouts.push(quote! {
+                let inner = <#ty as #shader_fields>::FIELDS;
+                let mut idx = 0;
+                while idx < inner.len() {
+                    fields[#start + idx] = inner[idx];
+                    idx += 1;
+                }
+            });
+            writes.push(quote! {
+                <#ty as #shader_flatten>::write_fields(
+                    &self.#member,
+                    bytes,
+                    &fields[#start..][..#count],
+                );
+            });
+            start = quote!(#start + #count);
+        } else {
+            let name = &field.name;
+            let field_align = field_align(field, &shader_repr);
+            let size = match &field_size {
+                None => quote!(::core::mem::size_of::<<#ty as #shader_repr>::Repr>()),
+                Some(size) => quote!(<#ty as ::shader_data::#size<::shader_data::#layout>>::SIZE),
+            };
+            let value = field.value(&quote!(&self.#member));
+            let end_align = match &field_size {
+                None => quote!(1),
+                Some(size) => {
+                    quote!(<#ty as ::shader_data::#size<::shader_data::#layout>>::END_ALIGN)
+                }
+            };
+            aligns.push(quote! {
+                aligns[#start] = #field_align;
+            });
+            end_aligns.push(quote! {
+                end_aligns[#start] = #end_align;
+            });
+            layouts.push(quote! {
+                fields[#start] = ::shader_data::FieldLayout {
+                    name: #name,
+                    offset: 0,
+                    size: #size,
+                };
+            });
+            writes.push(quote! {
+                fields[#start].write(bytes, <#ty as #shader_repr>::repr(#value));
+            });
+            start = quote!(#start + 1);
+        }
+    }
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #[derive(Clone, Copy)]
+        #[repr(C)]
+        #vis struct #repr {
+            align: #repr_align,
+            bytes: [::core::mem::MaybeUninit<u8>; #repr::SIZE],
+        }
+
+        impl #repr {
+            const COUNT: usize = #start;
+
+            const ALIGNS: [usize; #repr::COUNT] = {
+                let mut aligns = [0; #repr::COUNT];
+                #({ #aligns })*
+                aligns
+            };
+
+            const END_ALIGNS: [usize; #repr::COUNT] = {
+                let mut end_aligns = [1; #repr::COUNT];
+                #({ #end_aligns })*
+                end_aligns
+            };
+
+            const FIELDS: [::shader_data::FieldLayout; #repr::COUNT] = {
+                let mut fields = [::shader_data::FieldLayout {
+                    name: "",
+                    offset: 0,
+                    size: 0,
+                }; #repr::COUNT];
+                #({ #layouts })*
+                #place
+            };
+
+            const SIZE: usize = ::shader_data::FieldLayout::struct_size(&#repr::FIELDS, #struct_size);
+        }
+
+        const _: () = {
+            ::core::assert!(
+                ::shader_data::FieldLayout::are_aligned(&#repr::FIELDS, &#repr::ALIGNS),
+                #aligned,
+            );
+            ::core::assert!(::shader_data::FieldLayout::are_ordered(&#repr::FIELDS), #ordered);
+            #layout_assertions
+        };
+
+        impl #shader_repr for #ident {
+            type Align = #repr_align;
+            type Repr = #repr;
+
+            #[inline(always)]
+            fn repr(&self) -> #repr {
+                let mut bytes = [::core::mem::MaybeUninit::new(0); #repr::SIZE];
+                <Self as #shader_flatten>::write_fields(self, &mut bytes, &#repr::FIELDS);
+                #repr {
+                    align: ::core::default::Default::default(),
+                    bytes,
+                }
+            }
+        }
+
+        impl #shader_fields for #ident {
+            const FIELDS: &'static [::shader_data::FieldLayout] = &#repr::FIELDS;
+        }
+
+        #size_impl
+
+        impl #shader_flatten for #ident {
+            type Align = #align;
+
+            const ALIGNS: &'static [usize] = &#repr::ALIGNS;
+
+            const END_ALIGNS: &'static [usize] = &#repr::END_ALIGNS;
+
+            #[inline(always)]
+            fn write_fields(
+                &self,
+                bytes: &mut [::core::mem::MaybeUninit<u8>],
+                fields: &[::shader_data::FieldLayout],
+            ) {
+                #(#writes)*
+            }
+        }
+    }
+}
+
+/// Implements `CodeGen` for the structure.
+///
+/// Generic structures get mangled name for each instantiation.
+fn derive_struct_codegen(input: &DeriveInput, fields: &[Field], lang: &Ident) -> TokenStream2 {
+    let ident = &input.ident;
+    let base_name = ident.to_string();
+
+    let codegen = quote!(::shader_data::CodeGen<::shader_data::#lang>);
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let mut where_clause = where_clause(input, fields, &codegen);
+    for param in type_params(input) {
+        where_clause.predicates.push(parse_quote!(#param: #codegen));
+    }
+
+    let args = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote!(<#ident as #codegen>::name()))
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                Some(quote!(::std::borrow::Cow::Owned(::std::string::ToString::to_string(&#ident))))
+            }
+            GenericParam::Lifetime(_) => None,
+        });
+
+    let name = if input.generics.params.is_empty() {
+        quote!(::std::borrow::Cow::Borrowed(#base_name))
+    } else {
+        quote!(::std::borrow::Cow::Owned(::shader_data::mangled_name(#base_name, &[#(#args,)*])))
+    };
+
+    let code_gen_fields = quote!(::shader_data::CodeGenFields<::shader_data::#lang>);
+    let pushes = fields.iter().map(|field| {
+        let ty = field.repr_ty();
+        if field.flatten {
+            quote!(fields.extend(<#ty as #code_gen_fields>::fields());)
+        } else {
+            let name = &field.name;
+            quote!(fields.push((#name, <#ty as #codegen>::name()));)
+        }
+    });
+    let layout_pushes = fields.iter().map(|field| {
+        let ty = field.repr_ty();
+        if field.flatten {
+            quote!(fields.extend(<#ty as #code_gen_fields>::layout_fields::<Layout>());)
+        } else {
+            let name = &field.name;
+            quote!(fields.push((#name, <#ty as #codegen>::member_name::<Layout>()));)
+        }
+    });
+
+    let packed_pushes = fields.iter().map(|field| {
+        let ty = field.repr_ty();
+        if field.flatten {
+            quote!(fields.extend(<#ty as #code_gen_fields>::packed_fields());)
+        } else {
+            let name = &field.name;
+            quote! {
+                if let ::std::option::Option::Some((unpack, result)) = <#ty as #codegen>::unpack() {
+                    fields.push((#name, unpack, result));
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #code_gen_fields for #ident #ty_generics #where_clause {
+            fn fields() -> ::std::vec::Vec<(&'static str, ::std::borrow::Cow<'static, str>)> {
+                let mut fields = ::std::vec::Vec::new();
+                #(#pushes)*
+                fields
+            }
+
+            fn layout_fields<Layout: ::shader_data::LayoutRules>(
+            ) -> ::std::vec::Vec<(&'static str, ::std::borrow::Cow<'static, str>)> {
+                let mut fields = ::std::vec::Vec::new();
+                #(#layout_pushes)*
+                fields
+            }
+
+            fn packed_fields() -> ::std::vec::Vec<(
+                &'static str,
+                ::std::borrow::Cow<'static, str>,
+                ::std::borrow::Cow<'static, str>,
+            )> {
+                let mut fields = ::std::vec::Vec::new();
+                #(#packed_pushes)*
+                fields
+            }
+        }
+
+        impl #impl_generics #codegen for #ident #ty_generics #where_clause {
+            #[inline(always)]
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                #name
+            }
+
+            fn definition() -> ::std::option::Option<::std::string::String> {
+                <Self as #codegen>::layout_definition::<::shader_data::DefaultLayout>()
+            }
+
+            fn layout_name<Layout: ::shader_data::LayoutRules>() -> ::std::borrow::Cow<'static, str> {
+                // Structures declared differently in the layout have separate definitions.
+                let name = <Self as #codegen>::name();
+                let definition = <::shader_data::#lang as ::shader_data::ShaderLang>::layout_struct_definition(
+                    &name,
+                    &<Self as #code_gen_fields>::layout_fields::<Layout>(),
+                    Layout::ALIGN16,
+                );
+                let default_definition = <::shader_data::#lang as ::shader_data::ShaderLang>::struct_definition(
+                    &name,
+                    &<Self as #code_gen_fields>::layout_fields::<::shader_data::DefaultLayout>(),
+                );
+                match definition == default_definition {
+                    true => name,
+                    false => ::std::borrow::Cow::Owned(::std::format!("{}{}", name, Layout::SUFFIX)),
+                }
+            }
+
+            fn layout_definition<Layout: ::shader_data::LayoutRules>(
+            ) -> ::std::option::Option<::std::string::String> {
+                let name = <Self as #codegen>::layout_name::<Layout>();
+                let mut definition = <::shader_data::#lang as ::shader_data::ShaderLang>::layout_struct_definition(
+                    &name,
+                    &<Self as #code_gen_fields>::layout_fields::<Layout>(),
+                    Layout::ALIGN16,
+                );
+                // Accessors unpack fields of packed types.
+                for (field, unpack, result) in <Self as #code_gen_fields>::packed_fields() {
+                    definition.push_str(
+                        &<::shader_data::#lang as ::shader_data::ShaderLang>::function_definition(
+                            &::std::format!("{}_{}", name, field),
+                            &[("value", &name)],
+                            &result,
+                            &::std::format!("{}(value.{})", unpack, field),
+                        ),
+                    );
+                }
+                ::std::option::Option::Some(definition)
+            }
+        }
+    }
+}
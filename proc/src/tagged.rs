@@ -0,0 +1,508 @@
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse_quote, spanned::Spanned, DataEnum, DeriveInput, Generics, Ident, Type, WhereClause,
+};
+
+use crate::{
+    enums::screaming_snake_case,
+    field::{check_fields, collect_fields, reject_flatten, Field},
+    lang::{languages, placed, struct_align, Lang},
+    structs::{repr_align, repr_struct, repr_value},
+};
+
+/// Variant of the tagged enum.
+struct Variant {
+    ident: Ident,
+
+    /// Fields of the variant that go into the shader.
+    fields: Vec<Field>,
+}
+
+/// Derives `ShaderRepr` for enum with `#[shader(tagged)]` attribute.
+///
+/// Such enums are represented by `u32` tag with index of the variant
+/// followed by payload large enough for any variant.
+/// Payload is aligned to at least 16 bytes, so that in the shader
+/// it can be declared as an array of 4-component unsigned vectors.
+pub fn derive_tagged(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream2> {
+    if let Some(param) = input.generics.params.first() {
+        return Err(syn::Error::new(
+            param.span(),
+            "`ShaderRepr` cannot be derived for generic tagged enums",
+        ));
+    }
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            Ok(Variant {
+                ident: variant.ident.clone(),
+                fields: collect_fields(&variant.fields, &[])?,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    for variant in &variants {
+        reject_flatten(&variant.fields, "tagged enums")?;
+        check_fields(&variant.fields)?;
+    }
+
+    let mut tokens = TokenStream2::new();
+
+    for lang in languages() {
+        tokens.extend(derive_tagged_repr(input, &variants, lang));
+        tokens.extend(lang.marker_impl(input, &field_types(&variants)));
+        tokens.extend(lang.element_marker_impl(input));
+
+        if cfg!(feature = "codegen") {
+            tokens.extend(derive_tagged_codegen(input, &variants, lang));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Types of the fields of all variants.
+fn field_types(variants: &[Variant]) -> Vec<Type> {
+    variants
+        .iter()
+        .flat_map(|variant| &variant.fields)
+        .map(|field| field.repr_ty().clone())
+        .collect()
+}
+
+fn variant_repr(input: &DeriveInput, variant: &Variant, lang: &Ident, layout: &Ident) -> Ident {
+    format_ident!("__{}{}{}{}Repr", input.ident, variant.ident, lang, layout)
+}
+
+/// Generates repr-types for the tagged enum and implements `ShaderRepr` and `ShaderFields` for it.
+///
+/// Payload is opaque to the shader and unpacked by generated functions,
+/// so variants are laid out with each layout of the language separately
+/// and the payload is large enough for any of them.
+/// This way all layouts share the same definition in the shader.
+fn derive_tagged_repr(input: &DeriveInput, variants: &[Variant], lang: &Lang) -> TokenStream2 {
+    if variants.iter().all(|variant| variant.fields.is_empty()) {
+        return derive_unit_tagged_repr(input, variants, lang);
+    }
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let layouts = lang.layouts;
+    let lang = format_ident!("{}", lang.ident);
+    let repr = format_ident!("__{}{}Repr", ident, lang);
+    let payload = format_ident!("__{}{}Payload", ident, lang);
+
+    let generics = Generics::default();
+    let where_clause: WhereClause = parse_quote!(where);
+
+    let mut tokens = TokenStream2::new();
+    let mut payload_fields = Vec::new();
+    let mut payload_aligns = Vec::new();
+    let mut impls = Vec::new();
+
+    for layout in layouts {
+        let layout = format_ident!("{}", layout);
+        let shader_repr =
+            quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+        let mut arms = Vec::new();
+
+        for (idx, variant) in variants.iter().enumerate() {
+            let variant_ident = &variant.ident;
+            let tag = Literal::u32_suffixed(idx as u32);
+
+            let bindings = variant
+                .fields
+                .iter()
+                .map(|field| format_ident!("__field_{}", field.ident))
+                .collect::<Vec<_>>();
+            let members = variant.fields.iter().map(|field| &field.member);
+
+            let value = if variant.fields.is_empty() {
+                quote!(#payload { __unit: () })
+            } else {
+                let variant_repr = variant_repr(input, variant, &lang, &layout);
+                let payload_field = format_ident!("{}_{}", variant_ident, layout);
+
+                tokens.extend(repr_struct(
+                    vis,
+                    &variant_repr,
+                    &generics,
+                    &where_clause,
+                    &variant.fields,
+                    &shader_repr,
+                    &quote!(),
+                ));
+
+                payload_fields.push(quote!(#payload_field: #variant_repr));
+                payload_aligns.push(repr_align(&variant.fields, &shader_repr));
+
+                let accesses = bindings
+                    .iter()
+                    .map(|binding| quote!(#binding))
+                    .collect::<Vec<_>>();
+                let value = repr_value(
+                    &variant_repr,
+                    &variant.fields,
+                    &accesses,
+                    &shader_repr,
+                    &quote!(),
+                );
+                quote!(#payload { #payload_field: #value })
+            };
+
+            arms.push(quote! {
+                #ident::#variant_ident { #(#members: #bindings,)* .. } => #repr {
+                    tag: ::shader_data::Aligned::new(#tag),
+                    payload: ::shader_data::Aligned::new(#value),
+                },
+            });
+        }
+
+        impls.push((layout, arms));
+    }
+
+    let payload_align = quote!((::shader_data::Align16, (#(#payload_aligns,)*)));
+
+    tokens.extend(quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_snake_case)]
+        #[derive(Clone, Copy)]
+        #[repr(C)]
+        #vis union #payload {
+            __unit: (),
+            #(#payload_fields,)*
+        }
+
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #[derive(Clone, Copy)]
+        #[repr(C)]
+        #vis struct #repr {
+            tag: ::shader_data::Aligned<::shader_data::Align4, u32>,
+            payload: ::shader_data::Aligned<#payload_align, #payload>,
+        }
+    });
+
+    for (layout, arms) in impls {
+        let shader_repr =
+            quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+        let shader_fields =
+            quote!(::shader_data::ShaderFields<::shader_data::#lang, ::shader_data::#layout>);
+
+        tokens.extend(quote! {
+            impl #shader_repr for #ident {
+                type Align = (::shader_data::Align4, #payload_align);
+                type Repr = #repr;
+
+                #[inline(always)]
+                fn repr(&self) -> #repr {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+
+            impl #shader_fields for #ident {
+                const FIELDS: &'static [::shader_data::FieldLayout] = &[
+                    ::shader_data::FieldLayout {
+                        name: "tag",
+                        offset: ::core::mem::offset_of!(#repr, tag),
+                        size: ::core::mem::size_of::<u32>(),
+                    },
+                    ::shader_data::FieldLayout {
+                        name: "payload",
+                        offset: ::core::mem::offset_of!(#repr, payload),
+                        size: ::core::mem::size_of::<::shader_data::Aligned<#payload_align, #payload>>(),
+                    },
+                ];
+            }
+        });
+
+        if let Some(placed) = placed(&layout.to_string()) {
+            let size = quote!(::core::mem::size_of::<<#ident as #shader_repr>::Repr>());
+            tokens.extend(placed.struct_size_impl(input, &parse_quote!(where), &layout, &size));
+        }
+    }
+
+    tokens
+}
+
+/// Implements `ShaderRepr` and `ShaderFields` for the tagged enum without fields in variants.
+///
+/// It has no payload and is represented by the tag alone,
+/// aligned as structures in the layout.
+fn derive_unit_tagged_repr(input: &DeriveInput, variants: &[Variant], lang: &Lang) -> TokenStream2 {
+    let ident = &input.ident;
+    let layouts = lang.layouts;
+    let lang = format_ident!("{}", lang.ident);
+    let variant_idents = variants.iter().map(|variant| &variant.ident);
+    let tags = (0..variants.len()).map(|idx| Literal::u32_suffixed(idx as u32));
+
+    let arms = quote! {
+        #(#ident::#variant_idents { .. } => #tags,)*
+    };
+
+    let mut tokens = TokenStream2::new();
+
+    for layout in layouts {
+        let placed = placed(layout);
+        let align = match placed.as_ref().map(|placed| placed.struct_align) {
+            Some(struct_align) => Some(struct_align),
+            None => struct_align(layout),
+        };
+        let align = match align {
+            None => quote!(::shader_data::Align4),
+            Some(align) => {
+                let align = format_ident!("{}", align);
+                quote!((::shader_data::#align, ::shader_data::Align4))
+            }
+        };
+
+        let layout = format_ident!("{}", layout);
+        let shader_repr =
+            quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+        let shader_fields =
+            quote!(::shader_data::ShaderFields<::shader_data::#lang, ::shader_data::#layout>);
+
+        tokens.extend(quote! {
+            impl #shader_repr for #ident {
+                type Align = #align;
+                type Repr = ::shader_data::Aligned<#align, u32>;
+
+                #[inline(always)]
+                fn repr(&self) -> Self::Repr {
+                    ::shader_data::Aligned::new(match self {
+                        #arms
+                    })
+                }
+            }
+
+            impl #shader_fields for #ident {
+                const FIELDS: &'static [::shader_data::FieldLayout] = &[
+                    ::shader_data::FieldLayout {
+                        name: "tag",
+                        offset: 0,
+                        size: ::core::mem::size_of::<u32>(),
+                    },
+                ];
+            }
+        });
+
+        if let Some(placed) = placed {
+            // Size of the structure is not rounded up in placed layouts.
+            let size = quote!(::core::mem::size_of::<u32>());
+            tokens.extend(placed.struct_size_impl(input, &parse_quote!(where), &layout, &size));
+        }
+    }
+
+    tokens
+}
+
+/// Implements `CodeGen` for the tagged enum.
+///
+/// Definition contains constants for tags, structure with tag and payload,
+/// and for each variant with fields a structure and functions
+/// that unpack it from the payload.
+///
+/// Variants are unpacked by `{Name}_as_{Variant}` according to the default layout of the language
+/// and by `{Name}_as_{Variant}_{Layout}` according to other layouts.
+/// Fields of the variants must implement `ShaderLoad`.
+fn derive_tagged_codegen(input: &DeriveInput, variants: &[Variant], lang: &Lang) -> TokenStream2 {
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let layouts = lang.layouts;
+    let default_layout = format_ident!("{}", layouts[0]);
+    let lang = format_ident!("{}", lang.ident);
+
+    let codegen = quote!(::shader_data::CodeGen<::shader_data::#lang>);
+    let shader_load = quote!(::shader_data::ShaderLoad<::shader_data::#lang>);
+    let shader_lang = quote!(<::shader_data::#lang as ::shader_data::ShaderLang>);
+    let shader_fields =
+        quote!(::shader_data::ShaderFields<::shader_data::#lang, ::shader_data::#default_layout>);
+
+    let prefix = screaming_snake_case(&name);
+    let constants = variants.iter().enumerate().map(|(idx, variant)| {
+        let constant = format!(
+            "{}_{}",
+            prefix,
+            screaming_snake_case(&variant.ident.to_string())
+        );
+        let idx = idx as u32;
+        quote! {
+            definition.push_str(&#shader_lang::constant_definition(
+                #constant,
+                &uint,
+                &#shader_lang::uint_literal(#idx),
+            ));
+        }
+    });
+
+    if variants.iter().all(|variant| variant.fields.is_empty()) {
+        return quote! {
+            impl #codegen for #ident {
+                #[inline(always)]
+                fn name() -> ::std::borrow::Cow<'static, str> {
+                    ::std::borrow::Cow::Borrowed(#name)
+                }
+
+                fn definition() -> ::std::option::Option<::std::string::String> {
+                    let uint = <u32 as #codegen>::name();
+                    let mut definition = ::std::string::String::new();
+                    #(#constants)*
+                    definition.push_str(&#shader_lang::struct_definition(#name, &[("tag", uint)]));
+                    ::std::option::Option::Some(definition)
+                }
+            }
+        };
+    }
+
+    let unpacks = variants
+        .iter()
+        .filter(|variant| !variant.fields.is_empty())
+        .map(|variant| {
+            let variant_name = format!("{}_{}", name, variant.ident);
+
+            let field_names = variant
+                .fields
+                .iter()
+                .map(|field| &field.name)
+                .collect::<Vec<_>>();
+            let field_types = variant
+                .fields
+                .iter()
+                .map(Field::repr_ty)
+                .collect::<Vec<_>>();
+
+            let functions = layouts.iter().enumerate().map(|(idx, layout)| {
+                let function = match idx {
+                    0 => format!("{}_as_{}", name, variant.ident),
+                    _ => format!("{}_as_{}_{}", name, variant.ident, layout),
+                };
+                let layout = format_ident!("{}", layout);
+                let variant_repr = variant_repr(input, variant, &lang, &layout);
+
+                let shader_repr =
+                    quote!(::shader_data::ShaderRepr<::shader_data::#lang, ::shader_data::#layout>);
+                let loads = variant.fields.iter().map(|field| {
+                    let name = &field.name;
+                    let field_ident = &field.ident;
+                    load_field(
+                        field.repr_ty(),
+                        &quote!(::std::string::String::from(#name)),
+                        &quote!(::core::mem::offset_of!(#variant_repr, #field_ident)),
+                        &shader_repr,
+                        &shader_load,
+                    )
+                });
+
+                quote! {
+                    let mut fields = ::std::vec::Vec::new();
+                    #(#loads)*
+                    let fields = fields
+                        .iter()
+                        .map(|(name, load): &(::std::string::String, ::std::string::String)| {
+                            (name.as_str(), load.clone())
+                        })
+                        .collect::<::std::vec::Vec<_>>();
+                    definition.push_str(&#shader_lang::unpack_function(
+                        #function,
+                        #name,
+                        #variant_name,
+                        &fields,
+                    ));
+                }
+            });
+
+            quote! {
+                definition.push_str(&#shader_lang::struct_definition(
+                    #variant_name,
+                    &[#((#field_names, <#field_types as #codegen>::name()),)*],
+                ));
+                #(#functions)*
+            }
+        });
+
+    quote! {
+        impl #codegen for #ident {
+            #[inline(always)]
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Borrowed(#name)
+            }
+
+            fn definition() -> ::std::option::Option<::std::string::String> {
+                let uint = <u32 as #codegen>::name();
+                let [tag, payload] = <Self as #shader_fields>::FIELDS else {
+                    ::core::unreachable!()
+                };
+
+                // Payload is declared as an array of 4-component vectors
+                // and placed after the tag and padding words.
+                // Its layout is the same in all layouts of the language.
+                let payload_type = #shader_lang::array_name(
+                    &<::shader_data::vec4u as #codegen>::name(),
+                    payload.size / 16,
+                );
+                let word = |idx: usize| ::std::format!("value.payload[{}][{}]", idx / 4, idx % 4);
+
+                let mut fields = ::std::vec![("tag".to_owned(), uint.clone())];
+                for idx in 0..(payload.offset - tag.offset - tag.size) / 4 {
+                    fields.push((::std::format!("_pad{}", idx), uint.clone()));
+                }
+                fields.push(("payload".to_owned(), ::std::borrow::Cow::Owned(payload_type)));
+                let fields = fields
+                    .iter()
+                    .map(|(name, ty)| (name.as_str(), ty.clone()))
+                    .collect::<::std::vec::Vec<_>>();
+
+                let mut definition = ::std::string::String::new();
+                #(#constants)*
+                definition.push_str(&#shader_lang::struct_definition(#name, &fields));
+                #(#unpacks)*
+                ::std::option::Option::Some(definition)
+            }
+        }
+    }
+}
+
+/// Statements that push pairs of place and expression that loads it
+/// for the field of type `ty` at byte offset `offset` into `fields`.
+///
+/// Arrays are loaded element by element, as not every language has array constructors.
+fn load_field(
+    ty: &Type,
+    place: &TokenStream2,
+    offset: &TokenStream2,
+    shader_repr: &TokenStream2,
+    shader_load: &TokenStream2,
+) -> TokenStream2 {
+    match ty {
+        Type::Array(array) => {
+            let len = &array.len;
+            let load = load_field(
+                &array.elem,
+                &quote!(::std::format!("{}[{}]", place, idx)),
+                &quote!(offset + idx * ::core::mem::size_of::<<#ty as #shader_repr>::Repr>() / (#len)),
+                shader_repr,
+                shader_load,
+            );
+            quote! {
+                let (place, offset) = (#place, #offset);
+                for idx in 0..(#len) {
+                    #load
+                }
+            }
+        }
+        Type::Group(group) => load_field(&group.elem, place, offset, shader_repr, shader_load),
+        Type::Paren(paren) => load_field(&paren.elem, place, offset, shader_repr, shader_load),
+        _ => {
+            // Error for the type that cannot be loaded points at the field type.
+            let load = quote_spanned!(ty.span()=> <#ty as #shader_load>::load);
+            quote! {
+                fields.push((#place, #load(&word, (#offset) / 4)));
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Derived types must compile with all shader languages enabled together.
+
+use std::mem::size_of;
+
+use shader_data::{
+    f16, mat2b, mat2x3, mat3h, mat4f, packed_vec3f, vec2, vec2u64, vec3, vec3b, vec3f, vec3h,
+    vec3i16, vec4, vec4u8, CodeGen, DefaultLayout, Glsl, Hlsl, HlslCBuffer, HlslCBufferRepr,
+    HlslCBufferRowMajor, HlslStructured, HostShareable, Msl, ShaderFields, ShaderRepr, Snorm16x2,
+    Std140, Std140RowMajor, Std430, Unorm8x4, Wgsl, WgslStorage, WgslUniform,
+};
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Foo {
+    a: u32,
+    b: vec3f,
+    c: mat4f,
+    d: [f32; 2],
+}
+
+#[test]
+fn glsl() {
+    assert_eq!(
+        <Foo as CodeGen<Glsl>>::definition().unwrap(),
+        "struct Foo {\n    uint a;\n    vec3 b;\n    mat4x4 c;\n    float d[2];\n};\n",
+    );
+
+    // Booleans occupy 4 bytes.
+    assert_eq!(
+        <vec3b as ShaderRepr<Glsl>>::repr(&vec3(true, false, true)).0,
+        [1, 0, 1]
+    );
+    assert_eq!(size_of::<<mat2b as ShaderRepr<Glsl, Std430>>::Repr>(), 16);
+
+    assert_eq!(<f16 as CodeGen<Glsl>>::name(), "float16_t");
+    assert_eq!(<vec3h as CodeGen<Glsl>>::name(), "f16vec3");
+    assert_eq!(size_of::<<mat3h as ShaderRepr<Glsl, Std430>>::Repr>(), 24);
+
+    assert_eq!(<vec4u8 as CodeGen<Glsl>>::name(), "u8vec4");
+    assert_eq!(<vec2u64 as CodeGen<Glsl>>::name(), "u64vec2");
+    assert_eq!(size_of::<<vec3i16 as ShaderRepr<Glsl, Std140>>::Repr>(), 6);
+}
+
+#[test]
+fn hlsl() {
+    assert_eq!(
+        <Foo as CodeGen<Hlsl>>::definition().unwrap(),
+        "struct Foo {\n    uint a;\n    float3 b;\n    row_major float4x4 c;\n    float d[2];\n};\n",
+    );
+
+    // Booleans occupy 4 bytes.
+    assert_eq!(size_of::<<vec3b as ShaderRepr<Hlsl>>::Repr>(), 12);
+    assert_eq!(<bool as HlslCBufferRepr>::SIZE, 4);
+
+    assert_eq!(<vec3h as CodeGen<Hlsl>>::name(), "float16_t3");
+    assert_eq!(<vec3h as HlslCBufferRepr>::SIZE, 6);
+
+    assert_eq!(<vec3i16 as CodeGen<Hlsl>>::name(), "int16_t3");
+    assert_eq!(<vec2u64 as CodeGen<Hlsl>>::name(), "uint64_t2");
+    assert_eq!(<vec2u64 as HlslCBufferRepr>::SIZE, 16);
+}
+
+#[test]
+fn msl() {
+    assert_eq!(
+        <Foo as CodeGen<Msl>>::definition().unwrap(),
+        "struct Foo {\n    uint a;\n    float3 b;\n    float4x4 c;\n    float d[2];\n};\n",
+    );
+    assert_eq!(<vec4<f16> as CodeGen<Msl>>::name(), "half4");
+    assert_eq!(<vec3h as CodeGen<Msl>>::name(), "half3");
+    assert_eq!(<packed_vec3f as CodeGen<Msl>>::name(), "packed_float3");
+    assert_eq!(<bool as CodeGen<Msl>>::name(), "bool");
+    assert_eq!(size_of::<<vec3b as ShaderRepr<Msl>>::Repr>(), 4);
+    assert_eq!(<vec4u8 as CodeGen<Msl>>::name(), "uchar4");
+    assert_eq!(<vec3i16 as CodeGen<Msl>>::name(), "short3");
+    assert_eq!(<vec2u64 as CodeGen<Msl>>::name(), "ulong2");
+    assert_eq!(size_of::<<vec3i16 as ShaderRepr<Msl>>::Repr>(), 8);
+
+    let offsets = <Foo as ShaderFields<Msl>>::FIELDS
+        .iter()
+        .map(|field| (field.offset, field.size))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [(0, 4), (16, 16), (32, 64), (96, 8)]);
+}
+
+#[test]
+fn wgsl() {
+    fn host_shareable<T: HostShareable>() {}
+    host_shareable::<Foo>();
+    host_shareable::<vec3h>();
+    assert_eq!(<vec3h as CodeGen<Wgsl>>::name(), "vec3<f16>");
+    assert_eq!(<mat2x3<f16> as CodeGen<Wgsl>>::name(), "mat2x3<f16>");
+
+    assert_eq!(
+        <Foo as CodeGen<Wgsl>>::definition().unwrap(),
+        "struct Foo {\n    a: u32,\n    b: vec3<f32>,\n    c: mat4x4<f32>,\n    d: array<f32, 2>,\n}\n",
+    );
+
+    let offsets = <Foo as ShaderFields<Wgsl, WgslStorage>>::FIELDS
+        .iter()
+        .map(|field| (field.offset, field.size))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [(0, 4), (16, 12), (32, 64), (96, 8)]);
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Light {
+    sphere: Sphere,
+    colors: [vec4<f32>; 2],
+    weights: [f32; 3],
+    intensity: f32,
+}
+
+#[test]
+fn wgsl_uniform() {
+    let offsets = <Light as ShaderFields<Wgsl, WgslStorage>>::FIELDS
+        .iter()
+        .map(|field| (field.offset, field.size))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [(0, 16), (16, 32), (48, 12), (60, 4)]);
+
+    // Scalars in arrays are padded to 16 bytes.
+    let offsets = <Light as ShaderFields<Wgsl, WgslUniform>>::FIELDS
+        .iter()
+        .map(|field| (field.offset, field.size))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [(0, 16), (16, 32), (48, 48), (96, 4)]);
+
+    assert_eq!(
+        <Light as CodeGen<Wgsl>>::definition().unwrap(),
+        "struct Light {\n    sphere: Sphere,\n    colors: array<vec4<f32>, 2>,\n    weights: array<f32, 3>,\n    intensity: f32,\n}\n",
+    );
+    assert_eq!(
+        <Light as CodeGen<Wgsl>>::layout_definition::<WgslUniform>().unwrap(),
+        "struct Light_Uniform {\n    @align(16) sphere: Sphere_Uniform,\n    colors: array<vec4<f32>, 2>,\n    weights: array<vec4<f32>, 3>,\n    intensity: f32,\n}\n",
+    );
+    assert_eq!(
+        <Sphere as CodeGen<Wgsl>>::layout_definition::<WgslUniform>().unwrap(),
+        "struct Sphere_Uniform {\n    @align(16) position: vec3<f32>,\n    radius: f32,\n}\n",
+    );
+
+    assert_eq!(
+        <[vec2<f32>; 2] as CodeGen<Wgsl>>::layout_name::<WgslUniform>(),
+        "array<vec4<f32>, 2>",
+    );
+
+    let light = Light {
+        sphere: Sphere {
+            position: vec3(1.0, 2.0, 3.0),
+            radius: 4.0,
+        },
+        colors: [vec4(1.0, 1.0, 1.0, 1.0); 2],
+        weights: [0.25, 0.5, 0.25],
+        intensity: 5.0,
+    };
+    let repr = ShaderRepr::<Wgsl, WgslUniform>::repr(&light);
+    assert_eq!(size_of_val(&repr), 112);
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Sphere {
+    position: vec3f,
+    radius: f32,
+}
+
+#[test]
+fn vec3_tail_padding() {
+    // Scalar is placed into the padding at the end of `vec3`,
+    // except in MSL where `float3` takes 16 bytes.
+    assert_eq!(Sphere::offset_of::<Glsl, Std140>("radius"), 12);
+    assert_eq!(Sphere::offset_of::<Glsl, Std430>("radius"), 12);
+    assert_eq!(Sphere::offset_of::<Hlsl, HlslStructured>("radius"), 12);
+    assert_eq!(Sphere::offset_of::<Hlsl, HlslCBuffer>("radius"), 12);
+    assert_eq!(Sphere::offset_of::<Msl, DefaultLayout>("radius"), 16);
+    assert_eq!(Sphere::offset_of::<Wgsl, WgslStorage>("radius"), 12);
+    assert_eq!(Sphere::offset_of::<Wgsl, WgslUniform>("radius"), 12);
+
+    assert_eq!(size_of::<<Sphere as ShaderRepr<Glsl, Std140>>::Repr>(), 16);
+    assert_eq!(
+        size_of::<<Sphere as ShaderRepr<Wgsl, WgslStorage>>::Repr>(),
+        16
+    );
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+#[shader(stride_align = 256)]
+struct Camera {
+    view: mat4f,
+    position: vec3f,
+}
+
+#[test]
+fn stride_align() {
+    // Size is rounded up so that each element of an array starts at a valid dynamic offset.
+    assert_eq!(size_of::<<Camera as ShaderRepr<Glsl, Std140>>::Repr>(), 256);
+    assert_eq!(size_of::<<Camera as ShaderRepr<Glsl, Std430>>::Repr>(), 256);
+    assert_eq!(<Camera as HlslCBufferRepr>::SIZE, 256);
+    assert_eq!(size_of::<<Camera as ShaderRepr<Msl>>::Repr>(), 256);
+    assert_eq!(
+        size_of::<<Camera as ShaderRepr<Wgsl, WgslUniform>>::Repr>(),
+        256
+    );
+    assert_eq!(
+        size_of::<<[Camera; 3] as ShaderRepr<Wgsl, WgslStorage>>::Repr>(),
+        768
+    );
+    assert_eq!(Camera::offset_of::<Glsl, Std140>("position"), 64);
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, ShaderRepr)]
+#[shader(bitflags)]
+struct MaterialFlags {
+    #[shader(rename = "alphaTest")]
+    alpha_test: bool,
+    double_sided: bool,
+}
+
+#[test]
+fn constant_names() {
+    // Bit constants follow the naming of enum constants.
+    let definition = <MaterialFlags as CodeGen<Glsl>>::definition().unwrap();
+    assert!(definition.contains("const uint MATERIAL_FLAGS_ALPHA_TEST = 1u;\n"));
+    assert!(definition.contains("const uint MATERIAL_FLAGS_DOUBLE_SIDED = 2u;\n"));
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Transform {
+    model: Foo,
+    scale: f32,
+}
+
+#[test]
+fn matrix_layout() {
+    assert_eq!(
+        <Transform as CodeGen<Hlsl>>::layout_definition::<HlslCBuffer>().unwrap(),
+        "struct Transform {\n    Foo model;\n    float scale;\n};\n",
+    );
+    assert_eq!(
+        <Transform as CodeGen<Hlsl>>::layout_definition::<HlslCBufferRowMajor>().unwrap(),
+        "struct Transform_RowMajor {\n    Foo_RowMajor model;\n    float scale;\n};\n",
+    );
+    assert_eq!(
+        <Foo as CodeGen<Hlsl>>::layout_definition::<HlslCBufferRowMajor>().unwrap(),
+        "struct Foo_RowMajor {\n    uint a;\n    float3 b;\n    column_major float4x4 c;\n    float d[2];\n};\n",
+    );
+
+    // Order of the matrices is set for the whole block in GLSL.
+    assert_eq!(
+        <Transform as CodeGen<Glsl>>::layout_definition::<Std140RowMajor>(),
+        <Transform as CodeGen<Glsl>>::definition(),
+    );
+    assert_eq!(
+        Glsl::block_definition::<Transform, Std140RowMajor>("uniform", "Transforms", None),
+        "layout(std140, row_major) uniform Transforms {\n    Foo model;\n    float scale;\n};\n",
+    );
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Surface {
+    color: Unorm8x4,
+    n: Snorm16x2,
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Material {
+    #[shader(flatten)]
+    surface: Surface,
+    roughness: f32,
+}
+
+#[test]
+fn packed_accessors() {
+    assert_eq!(
+        <Surface as CodeGen<Glsl>>::definition().unwrap(),
+        "struct Surface {\n    uint color;\n    uint n;\n};\n\
+         vec4 Surface_color(Surface value) {\n    return Unorm8x4_unpack(value.color);\n}\n\
+         vec2 Surface_n(Surface value) {\n    return Snorm16x2_unpack(value.n);\n}\n",
+    );
+    assert_eq!(
+        <Surface as CodeGen<Hlsl>>::definition().unwrap(),
+        "struct Surface {\n    uint color;\n    uint n;\n};\n\
+         float4 Surface_color(Surface value) {\n    return Unorm8x4_unpack(value.color);\n}\n\
+         float2 Surface_n(Surface value) {\n    return Snorm16x2_unpack(value.n);\n}\n",
+    );
+
+    // Flattened fields are unpacked by accessors of the structure they are inlined into.
+    assert_eq!(
+        <Material as CodeGen<Wgsl>>::definition().unwrap(),
+        "struct Material {\n    color: u32,\n    n: u32,\n    roughness: f32,\n}\n\
+         fn Material_color(value: Material) -> vec4<f32> {\n    return Unorm8x4_unpack(value.color);\n}\n\
+         fn Material_n(value: Material) -> vec2<f32> {\n    return Snorm16x2_unpack(value.n);\n}\n",
+    );
+    assert!(
+        <Material as CodeGen<Wgsl>>::layout_definition::<WgslUniform>()
+            .unwrap()
+            .contains("fn Material_Uniform_n(value: Material_Uniform) -> vec2<f32> {")
+    );
+}
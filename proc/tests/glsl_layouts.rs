@@ -0,0 +1,157 @@
+//! Offsets of the fields in GLSL layouts as defined by the OpenGL specification.
+
+use std::mem::size_of;
+
+use shader_data::{
+    mat2f, mat3f, vec2f, vec3f, FieldLayout, Glsl, Scalar, ShaderFields, ShaderRepr, Std140, Std430,
+};
+
+/// Structure that ends at 20 bytes.
+#[derive(Clone, Copy, ShaderRepr)]
+struct Small {
+    a: f32,
+    b: vec2f,
+    c: f32,
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Outer {
+    a: f32,
+    small: Small,
+    b: f32,
+    smalls: [Small; 2],
+    c: f32,
+    floats: [f32; 3],
+    d: f32,
+    v2s: [vec2f; 2],
+    v3: vec3f,
+    e: f32,
+    m3: mat3f,
+    f: f32,
+    m2: mat2f,
+    g: f32,
+}
+
+fn layout<T, Layout>() -> Vec<(&'static str, usize, usize)>
+where
+    T: ShaderFields<Glsl, Layout>,
+{
+    T::FIELDS
+        .iter()
+        .map(|&FieldLayout { name, offset, size }| (name, offset, size))
+        .collect()
+}
+
+fn size<T, Layout>() -> usize
+where
+    T: ShaderRepr<Glsl, Layout>,
+{
+    size_of::<T::Repr>()
+}
+
+#[test]
+fn std140_struct() {
+    assert_eq!(
+        layout::<Small, Std140>(),
+        [("a", 0, 4), ("b", 8, 8), ("c", 16, 4)],
+    );
+
+    // Size of the structure is rounded up to 16.
+    assert_eq!(size::<Small, Std140>(), 32);
+}
+
+#[test]
+fn std140_arrays() {
+    // Array element stride is rounded up to 16.
+    assert_eq!(size::<[f32; 3], Std140>(), 48);
+    assert_eq!(size::<[vec2f; 2], Std140>(), 32);
+    assert_eq!(size::<[vec3f; 2], Std140>(), 32);
+    assert_eq!(size::<[[f32; 2]; 3], Std140>(), 96);
+    assert_eq!(size::<[Small; 2], Std140>(), 64);
+    assert_eq!(size::<[mat2f; 2], Std140>(), 64);
+}
+
+#[test]
+fn std140_nested() {
+    assert_eq!(
+        layout::<Outer, Std140>(),
+        [
+            ("a", 0, 4),
+            ("small", 16, 32),
+            ("b", 48, 4),
+            ("smalls", 64, 64),
+            ("c", 128, 4),
+            ("floats", 144, 48),
+            ("d", 192, 4),
+            ("v2s", 208, 32),
+            ("v3", 240, 12),
+            ("e", 252, 4),
+            ("m3", 256, 48),
+            ("f", 304, 4),
+            ("m2", 320, 32),
+            ("g", 352, 4),
+        ],
+    );
+    assert_eq!(size::<Outer, Std140>(), 368);
+}
+
+#[test]
+fn std430_struct() {
+    assert_eq!(
+        layout::<Small, Std430>(),
+        [("a", 0, 4), ("b", 8, 8), ("c", 16, 4)],
+    );
+
+    // Size of the structure is rounded up to its alignment.
+    assert_eq!(size::<Small, Std430>(), 24);
+}
+
+#[test]
+fn std430_arrays() {
+    // Array element stride is the size of the element rounded up to its alignment.
+    assert_eq!(size::<[f32; 3], Std430>(), 12);
+    assert_eq!(size::<[vec2f; 2], Std430>(), 16);
+    assert_eq!(size::<[vec3f; 2], Std430>(), 32);
+    assert_eq!(size::<[[f32; 2]; 3], Std430>(), 24);
+    assert_eq!(size::<[Small; 2], Std430>(), 48);
+    assert_eq!(size::<[mat2f; 2], Std430>(), 32);
+}
+
+#[test]
+fn std430_nested() {
+    assert_eq!(
+        layout::<Outer, Std430>(),
+        [
+            ("a", 0, 4),
+            ("small", 8, 24),
+            ("b", 32, 4),
+            ("smalls", 40, 48),
+            ("c", 88, 4),
+            ("floats", 92, 12),
+            ("d", 104, 4),
+            ("v2s", 112, 16),
+            ("v3", 128, 12),
+            ("e", 140, 4),
+            ("m3", 144, 48),
+            ("f", 192, 4),
+            ("m2", 200, 16),
+            ("g", 216, 4),
+        ],
+    );
+    assert_eq!(size::<Outer, Std430>(), 224);
+}
+
+#[test]
+fn scalar_block() {
+    assert_eq!(
+        Glsl::block_definition::<Small, Scalar>("buffer", "Data", None),
+        "layout(scalar) buffer Data {\n    float a;\n    vec2 b;\n    float c;\n};\n",
+    );
+
+    // Scalar block layout requires an extension.
+    assert_eq!(
+        Glsl::extension_directive::<Scalar>().as_deref(),
+        Some("#extension GL_EXT_scalar_block_layout : require\n"),
+    );
+    assert_eq!(Glsl::extension_directive::<Std430>(), None);
+}
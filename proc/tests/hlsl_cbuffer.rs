@@ -0,0 +1,165 @@
+//! Offsets of the fields in HLSL constant buffers as defined by the packing rules.
+
+use std::mem::size_of;
+
+use shader_data::{
+    mat2x3, mat3x4, vec2f, vec3f, vec4f, FieldLayout, Hlsl, HlslCBuffer, HlslCBufferRepr,
+    HlslCBufferRowMajor, ShaderFields, ShaderRepr,
+};
+
+fn layout<T, Layout>() -> Vec<(&'static str, usize, usize)>
+where
+    T: ShaderFields<Hlsl, Layout>,
+{
+    T::FIELDS
+        .iter()
+        .map(|&FieldLayout { name, offset, size }| (name, offset, size))
+        .collect()
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Vectors {
+    a: vec2f,
+    b: vec3f,
+    c: f32,
+    d: vec2f,
+    e: vec2f,
+}
+
+#[test]
+fn register_boundary() {
+    // `float3` after `float2` does not fit into the rest of the register,
+    // while `float` and `float2` are packed into the free space.
+    assert_eq!(
+        layout::<Vectors, HlslCBuffer>(),
+        [
+            ("a", 0, 8),
+            ("b", 16, 12),
+            ("c", 28, 4),
+            ("d", 32, 8),
+            ("e", 40, 8),
+        ],
+    );
+    assert_eq!(<Vectors as HlslCBufferRepr>::SIZE, 48);
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Arrays {
+    a: f32,
+    floats: [f32; 3],
+    b: f32,
+    v3s: [vec3f; 2],
+    c: f32,
+    v4s: [vec4f; 2],
+    d: f32,
+}
+
+#[test]
+fn arrays() {
+    // Arrays start in a new register and each element takes its own register,
+    // but the last element is not padded so the scalar after it is packed into its register.
+    assert_eq!(
+        layout::<Arrays, HlslCBuffer>(),
+        [
+            ("a", 0, 4),
+            ("floats", 16, 36),
+            ("b", 52, 4),
+            ("v3s", 64, 28),
+            ("c", 92, 4),
+            ("v4s", 96, 32),
+            ("d", 128, 4),
+        ],
+    );
+    assert_eq!(<Arrays as HlslCBufferRepr>::SIZE, 132);
+    assert_eq!(<[f32; 3] as HlslCBufferRepr>::SIZE, 36);
+    assert_eq!(<[f32; 0] as HlslCBufferRepr>::SIZE, 0);
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Matrices {
+    a: f32,
+    m: mat2x3<f32>,
+    b: f32,
+    n: mat3x4<f32>,
+    c: f32,
+}
+
+#[test]
+fn matrices() {
+    // Matrices start in a new register and each vector takes its own register,
+    // but the last vector is not padded.
+    assert_eq!(
+        layout::<Matrices, HlslCBuffer>(),
+        [
+            ("a", 0, 4),
+            ("m", 16, 28),
+            ("b", 44, 4),
+            ("n", 48, 48),
+            ("c", 96, 4),
+        ],
+    );
+
+    // Row-major matrices are transposed.
+    assert_eq!(
+        layout::<Matrices, HlslCBufferRowMajor>(),
+        [
+            ("a", 0, 4),
+            ("m", 16, 40),
+            ("b", 56, 4),
+            ("n", 64, 60),
+            ("c", 124, 4),
+        ],
+    );
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Inner {
+    a: f32,
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Outer {
+    a: f32,
+    inner: Inner,
+    b: f32,
+    inners: [Inner; 2],
+    c: f32,
+}
+
+#[test]
+fn structures() {
+    // Structures start in a new register and members after them start in the next register.
+    assert_eq!(<Inner as HlslCBufferRepr>::SIZE, 4);
+    assert_eq!(
+        layout::<Outer, HlslCBuffer>(),
+        [
+            ("a", 0, 4),
+            ("inner", 16, 4),
+            ("b", 32, 4),
+            ("inners", 48, 20),
+            ("c", 80, 4),
+        ],
+    );
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Generic<T> {
+    a: vec3f,
+    b: T,
+    c: vec4f,
+}
+
+#[test]
+fn generic() {
+    // Generic structures are laid out with Rust alignment
+    // and their placement is checked when fields are used.
+    assert_eq!(
+        layout::<Generic<f32>, HlslCBuffer>(),
+        [("a", 0, 12), ("b", 12, 4), ("c", 16, 16)],
+    );
+    assert_eq!(<Generic<u32> as HlslCBufferRepr>::SIZE, 32);
+    assert_eq!(
+        size_of::<<Generic<f32> as ShaderRepr<Hlsl, HlslCBuffer>>::Repr>(),
+        32
+    );
+}
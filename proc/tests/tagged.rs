@@ -0,0 +1,119 @@
+//! Layout and generated code of the tagged enums.
+
+use std::mem::size_of;
+
+use shader_data::{
+    vec, vec2f, vec3f, CodeGen, FieldLayout, Glsl, ShaderFields, ShaderRepr, Std140, Std430,
+};
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, ShaderRepr)]
+#[shader(tagged)]
+enum Shape {
+    Empty,
+    Circle { center: vec2f, radius: f32 },
+    Polyline { points: [f32; 3], color: vec3f },
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, ShaderRepr)]
+#[shader(tagged)]
+enum Mode {
+    Off,
+    On,
+}
+
+fn layout<T, Layout>() -> Vec<(&'static str, usize, usize)>
+where
+    T: ShaderFields<Glsl, Layout>,
+{
+    T::FIELDS
+        .iter()
+        .map(|&FieldLayout { name, offset, size }| (name, offset, size))
+        .collect()
+}
+
+/// Reads the 32-bit words of the repr-value with given indices.
+fn words<T, Layout>(value: &T, indices: impl IntoIterator<Item = usize>) -> Vec<u32>
+where
+    T: ShaderRepr<Glsl, Layout>,
+{
+    let repr = value.repr();
+    indices
+        .into_iter()
+        .map(|idx| {
+            assert!((idx + 1) * 4 <= size_of::<T::Repr>());
+            // SAFETY: Word is in bounds and repr-types are aligned to at least 4 bytes.
+            unsafe { (&repr as *const T::Repr).cast::<u32>().add(idx).read() }
+        })
+        .collect()
+}
+
+#[test]
+fn payload_per_layout() {
+    // Payload fits the largest variant in any layout, so the shader definition is shared.
+    assert_eq!(
+        layout::<Shape, Std140>(),
+        [("tag", 0, 4), ("payload", 16, 64)],
+    );
+    assert_eq!(layout::<Shape, Std430>(), layout::<Shape, Std140>());
+
+    let shape = Shape::Polyline {
+        points: [1.0, 2.0, 3.0],
+        color: vec([4.0, 5.0, 6.0]),
+    };
+
+    let mut expected = vec![2];
+    expected.extend([1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0].map(f32::to_bits));
+
+    // Array elements are padded to 16 bytes in `Std140`.
+    assert_eq!(
+        words::<_, Std140>(&shape, [0, 4, 8, 12, 16, 17, 18]),
+        expected,
+    );
+
+    // And tightly packed in `Std430`.
+    assert_eq!(words::<_, Std430>(&shape, [0, 4, 5, 6, 8, 9, 10]), expected,);
+}
+
+#[test]
+fn unpack_per_layout() {
+    let definition = <Shape as CodeGen<Glsl>>::definition().unwrap();
+
+    assert!(definition.contains(
+        "struct Shape {\n    uint tag;\n    uint _pad0;\n    uint _pad1;\n    uint _pad2;\n    uvec4 payload[4];\n};\n"
+    ));
+    assert!(definition.contains(
+        "Shape_Polyline Shape_as_Polyline(Shape value) {\n    \
+        Shape_Polyline result;\n    \
+        result.points[0] = uintBitsToFloat(value.payload[0][0]);\n    \
+        result.points[1] = uintBitsToFloat(value.payload[1][0]);\n    \
+        result.points[2] = uintBitsToFloat(value.payload[2][0]);\n    \
+        result.color = vec3(uintBitsToFloat(value.payload[3][0]), uintBitsToFloat(value.payload[3][1]), uintBitsToFloat(value.payload[3][2]));\n    \
+        return result;\n}\n"
+    ));
+    assert!(definition.contains(
+        "Shape_Polyline Shape_as_Polyline_Std430(Shape value) {\n    \
+        Shape_Polyline result;\n    \
+        result.points[0] = uintBitsToFloat(value.payload[0][0]);\n    \
+        result.points[1] = uintBitsToFloat(value.payload[0][1]);\n    \
+        result.points[2] = uintBitsToFloat(value.payload[0][2]);\n    \
+        result.color = vec3(uintBitsToFloat(value.payload[1][0]), uintBitsToFloat(value.payload[1][1]), uintBitsToFloat(value.payload[1][2]));\n    \
+        return result;\n}\n"
+    ));
+}
+
+#[test]
+fn unit_variants() {
+    assert_eq!(layout::<Mode, Std430>(), [("tag", 0, 4)]);
+    assert_eq!(size_of::<<Mode as ShaderRepr<Glsl, Std430>>::Repr>(), 4);
+
+    // Structures are aligned to 16 bytes in `Std140`.
+    assert_eq!(size_of::<<Mode as ShaderRepr<Glsl, Std140>>::Repr>(), 16);
+    assert_eq!(words::<_, Std140>(&Mode::On, [0]), [1]);
+
+    assert_eq!(
+        <Mode as CodeGen<Glsl>>::definition().unwrap(),
+        "const uint MODE_OFF = 0u;\nconst uint MODE_ON = 1u;\nstruct Mode {\n    uint tag;\n};\n",
+    );
+}
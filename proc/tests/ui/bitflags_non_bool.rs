@@ -0,0 +1,10 @@
+use shader_data::ShaderRepr;
+
+#[derive(ShaderRepr)]
+#[shader(bitflags)]
+struct Flags {
+    visible: bool,
+    layer: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use shader_data::ShaderRepr;
+
+#[derive(Clone, Copy, ShaderRepr)]
+enum Negative {
+    A = -1,
+    B,
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+enum Large {
+    A = 0xffff_ffff,
+    B,
+}
+
+fn main() {}
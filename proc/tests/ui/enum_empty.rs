@@ -0,0 +1,6 @@
+use shader_data::ShaderRepr;
+
+#[derive(Clone, Copy, ShaderRepr)]
+enum Empty {}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use shader_data::ShaderRepr;
+
+#[derive(Clone, Copy, ShaderRepr)]
+#[repr(u64)]
+enum Mode {
+    A,
+    B,
+}
+
+fn main() {}
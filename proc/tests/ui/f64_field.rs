@@ -0,0 +1,9 @@
+use shader_data::{vec3, ShaderRepr};
+
+#[derive(ShaderRepr)]
+struct Foo {
+    a: f32,
+    b: vec3<f64>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use shader_data::{vec4f, ShaderRepr};
+
+#[derive(ShaderRepr)]
+struct Inner {
+    color: vec4f,
+}
+
+#[derive(ShaderRepr)]
+struct Outer<T> {
+    value: T,
+    #[shader(flatten)]
+    inner: Inner,
+}
+
+fn main() {}
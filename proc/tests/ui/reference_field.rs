@@ -0,0 +1,8 @@
+use shader_data::{vec4f, ShaderRepr};
+
+#[derive(ShaderRepr)]
+struct Foo {
+    a: &'static vec4f,
+}
+
+fn main() {}
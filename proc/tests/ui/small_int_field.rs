@@ -0,0 +1,9 @@
+use shader_data::{vec4u8, ShaderRepr};
+
+#[derive(ShaderRepr)]
+struct Foo {
+    color: vec4u8,
+    index: u16,
+}
+
+fn main() {}
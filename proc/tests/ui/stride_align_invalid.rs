@@ -0,0 +1,9 @@
+use shader_data::{mat4f, ShaderRepr};
+
+#[derive(ShaderRepr)]
+#[shader(stride_align = 100)]
+struct Camera {
+    view: mat4f,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use shader_data::{mat3f, ShaderRepr};
+
+#[derive(Clone, Copy, ShaderRepr)]
+#[shader(tagged)]
+enum Transform {
+    Identity,
+    Matrix { matrix: mat3f },
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use shader_data::{Glsl, ShaderRepr, Std140};
+
+#[derive(ShaderRepr)]
+struct Foo {
+    a: f32,
+}
+
+const OFFSET: usize = Foo::offset_of::<Glsl, Std140>("b");
+
+fn main() {
+    let _ = OFFSET;
+}
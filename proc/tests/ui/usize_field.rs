@@ -0,0 +1,9 @@
+use shader_data::ShaderRepr;
+
+#[derive(ShaderRepr)]
+struct Foo {
+    a: f32,
+    b: usize,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use shader_data::ShaderRepr;
+
+#[derive(ShaderRepr)]
+struct Foo {
+    enabled: bool,
+    #[shader(as = u32)]
+    visible: bool,
+}
+
+fn main() {}
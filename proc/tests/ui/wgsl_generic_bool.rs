@@ -0,0 +1,12 @@
+use shader_data::{ShaderRepr, Wgsl};
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn upload<T: ShaderRepr<Wgsl>>(_: &T) {}
+
+fn main() {
+    upload(&Wrapper { value: true });
+}
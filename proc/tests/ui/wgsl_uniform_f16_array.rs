@@ -0,0 +1,8 @@
+use shader_data::{f16, ShaderRepr};
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Weights {
+    values: [f16; 4],
+}
+
+fn main() {}
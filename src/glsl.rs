@@ -7,122 +7,234 @@ use crate::*;
 
 pub enum Glsl {}
 
+/// Layout of the uniform blocks.
+///
+/// Arrays have element stride rounded up to 16 bytes
+/// and structures have alignment and size rounded up to 16 bytes.
 pub type Std140 = DefaultLayout;
 
+/// Layout of the shader storage blocks.
+///
+/// Arrays and structures are aligned as their elements and fields.
 pub enum Std430 {}
 
+/// Scalar block layout from `GL_EXT_scalar_block_layout`.
+///
+/// Every type is aligned as its scalar components,
+/// so `vec3` takes 12 bytes and can be followed directly by a `float`.
+/// Arrays and matrix columns are tightly packed.
+pub enum Scalar {}
+
+/// `Std140` layout with row-major matrices.
+pub type Std140RowMajor = RowMajor<Std140>;
+
+/// `Std430` layout with row-major matrices.
+pub type Std430RowMajor = RowMajor<Std430>;
+
+/// `Scalar` layout with row-major matrices.
+pub type ScalarRowMajor = RowMajor<Scalar>;
+
+/// Layout of the GLSL interface blocks.
+pub trait GlslLayout: LayoutRules {
+    /// Layout qualifier of the block.
+    const QUALIFIER: &'static str;
+
+    /// Extension required by the layout, if any.
+    const EXTENSION: Option<&'static str> = None;
+}
+
+impl GlslLayout for Std140 {
+    const QUALIFIER: &'static str = "std140";
+}
+
+impl LayoutRules for Std430 {
+    const SUFFIX: &'static str = "_Std430";
+}
+
+impl LayoutRules for Scalar {
+    const SUFFIX: &'static str = "_Scalar";
+}
+
+impl GlslLayout for Std430 {
+    const QUALIFIER: &'static str = "std430";
+}
+
+impl GlslLayout for Scalar {
+    const QUALIFIER: &'static str = "scalar";
+    const EXTENSION: Option<&'static str> = Some("GL_EXT_scalar_block_layout");
+}
+
+impl<L> GlslLayout for RowMajor<L>
+where
+    L: GlslLayout,
+{
+    const QUALIFIER: &'static str = L::QUALIFIER;
+    const EXTENSION: Option<&'static str> = L::EXTENSION;
+}
+
+// `bool` occupies 4 bytes in GLSL buffers.
+shader_scalar!(in Glsl as u32 => {
+    bool | x1 = Align4, x2 = Align8, x4 = Align16;
+});
+
+// 8-, 16- and 64-bit types require `GL_EXT_shader_explicit_arithmetic_types`
+// and `GL_EXT_shader_8bit_storage` or `GL_EXT_shader_16bit_storage` for buffers.
 shader_scalar!(in Glsl => {
-    bool | x1 = Align1, x2 = Align2, x4 = Align4;
+    i8 as int8_t | x1 = Align1, x2 = Align2, x4 = Align4;
+    u8 as uint8_t | x1 = Align1, x2 = Align2, x4 = Align4;
+    i16 as int16_t | x1 = Align2, x2 = Align4, x4 = Align8;
+    u16 as uint16_t | x1 = Align2, x2 = Align4, x4 = Align8;
     i32 as int | x1 = Align4, x2 = Align8, x4 = Align16;
     u32 as uint | x1 = Align4, x2 = Align8, x4 = Align16;
+    f16 as float16_t | x1 = Align2, x2 = Align4, x4 = Align8;
     f32 as float | x1 = Align4, x2 = Align8, x4 = Align16;
     f64 as double | x1 = Align8, x2 = Align16, x4 = Align32;
+    i64 as int64_t | x1 = Align8, x2 = Align16, x4 = Align32;
+    u64 as uint64_t | x1 = Align8, x2 = Align16, x4 = Align32;
 });
 
-macro_rules! vec_mat_repr {
-    ($($t:ty)+) => {$(
-        impl<Std> ShaderRepr<Glsl, Std> for vec<$t, 2>
+macro_rules! vec_repr {
+    (in $layout:ident => $t:ty) => {
+        impl ShaderRepr<Glsl, $layout> for vec<$t, 2>
         where
             $t: ShaderScalar<Glsl>,
         {
             type Align = align!(in Glsl, $t, x2);
-            type Repr = Self;
+            type Repr = vec<<$t as ShaderRepr<Glsl>>::Repr, 2>;
 
             #[inline(always)]
-            fn repr(&self) -> Self {
-                *self
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))
             }
         }
 
-        impl<Std> ShaderRepr<Glsl, Std> for vec<$t, 3>
+        impl ShaderRepr<Glsl, $layout> for vec<$t, 3>
         where
             $t: ShaderScalar<Glsl>,
         {
             type Align = align!(in Glsl, $t, x4);
-            type Repr = Self;
+            type Repr = vec<<$t as ShaderRepr<Glsl>>::Repr, 3>;
 
             #[inline(always)]
-            fn repr(&self) -> Self {
-                *self
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))
             }
         }
 
-        impl<Std> ShaderRepr<Glsl, Std> for vec<$t, 4>
+        impl ShaderRepr<Glsl, $layout> for vec<$t, 4>
         where
             $t: ShaderScalar<Glsl>,
         {
             type Align = align!(in Glsl, $t, x4);
-            type Repr = Self;
+            type Repr = vec<<$t as ShaderRepr<Glsl>>::Repr, 4>;
 
             #[inline(always)]
-            fn repr(&self) -> Self {
-                *self
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))
+            }
+        }
+    };
+    ($($t:ty)+) => {$(
+        vec_repr!(in Std140 => $t);
+        vec_repr!(in Std430 => $t);
+
+        /// In scalar layout vectors are aligned as their components.
+        impl<const N: usize> ShaderRepr<Glsl, Scalar> for vec<$t, N>
+        where
+            $t: ShaderScalar<Glsl>,
+        {
+            type Align = align!(in Glsl, $t);
+            type Repr = vec<<$t as ShaderRepr<Glsl>>::Repr, N>;
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))
+            }
+        }
+    )*};
+}
+
+vec_repr! { bool i8 u8 i16 u16 i32 u32 i64 u64 f16 f32 f64 }
+
+macro_rules! mat_repr {
+    ($($t:ty)+) => {$(
+        /// In scalar layout matrix columns are tightly packed vectors.
+        impl<const N: usize, const M: usize> ShaderRepr<Glsl, Scalar> for mat<$t, N, M>
+        where
+            $t: ShaderScalar<Glsl>,
+        {
+            type Align = align!(in Glsl, $t);
+            type Repr = mat<<$t as ShaderRepr<Glsl>>::Repr, N, M>;
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                mat(self.0.map(|v| v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std140> for mat<$t, N, 2> {
-            type Repr = [Aligned<Self::Align, vec<$t, 2>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 2>>; N];
             type Align = (Align16, align!(in Glsl, $t, x2));
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std140> for mat<$t, N, 3> {
             type Align = (Align16, align!(in Glsl, $t, x4));
-            type Repr = [Aligned<Self::Align, vec<$t, 3>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 3>>; N];
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std140> for mat<$t, N, 4> {
             type Align = (Align16, align!(in Glsl, $t, x4));
-            type Repr = [Aligned<Self::Align, vec<$t, 4>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 4>>; N];
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std430> for mat<$t, N, 2> {
             type Align = align!(in Glsl, $t, x2);
-            type Repr = [Aligned<Self::Align, vec<$t, 2>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 2>>; N];
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std430> for mat<$t, N, 3> {
             type Align = align!(in Glsl, $t, x4);
-            type Repr = [Aligned<Self::Align, vec<$t, 3>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 3>>; N];
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
 
         impl<const N: usize> ShaderRepr<Glsl, Std430> for mat<$t, N, 4> {
             type Align = align!(in Glsl, $t, x4);
-            type Repr = [Aligned<Self::Align, vec<$t, 4>>; N];
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Glsl>>::Repr, 4>>; N];
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                self.0.map(|v| Aligned::new(vec(v)))
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Glsl>>::repr(&c)))))
             }
         }
     )*};
 }
 
-vec_mat_repr! { bool i32 u32 f32 f64 }
+mat_repr! { bool i32 u32 f16 f32 f64 }
 
 impl<T, const N: usize> ShaderRepr<Glsl, Std140> for [T; N]
 where
@@ -178,6 +290,117 @@ where
     }
 }
 
+impl<T, const N: usize> ShaderRepr<Glsl, Scalar> for [T; N]
+where
+    T: ShaderRepr<Glsl, Scalar>,
+{
+    type Align = T::Align;
+    type Repr = [Aligned<Self::Align, T::Repr>; N];
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        if TypeId::of::<T>() == TypeId::of::<T::Repr>()
+            && size_of::<Self::Repr>() == size_of::<Self>()
+        {
+            // SAFETY: Element type matches layout.
+            unsafe { core::mem::transmute_copy(self) }
+        } else {
+            // SAFETY: Array of `MaybeUninit` is always initialized.
+            let mut array: [MaybeUninit<_>; N] = unsafe { MaybeUninit::uninit().assume_init() };
+            for (i, elem) in self.iter().enumerate() {
+                array[i] = MaybeUninit::new(Aligned::new(elem.repr()));
+            }
+
+            // SAFETY: Every element of `array` was initialized.
+            array.map(|elem| unsafe { MaybeUninit::assume_init(elem) })
+        }
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl Glsl {
+    /// Definition of the interface block with fields of `T` laid out with `Layout`.
+    ///
+    /// `storage` is the storage qualifier of the block, like `uniform` or `buffer`.
+    /// Block has instance name if `instance` is some.
+    ///
+    /// `#extension` directive required by the layout is not included,
+    /// as it must precede other declarations in the shader.
+    /// Use [`Glsl::extension_directive`] to add it.
+    pub fn block_definition<T, Layout>(storage: &str, name: &str, instance: Option<&str>) -> String
+    where
+        T: CodeGenFields<Glsl> + ShaderRepr<Glsl, Layout>,
+        Layout: GlslLayout,
+    {
+        // Structure members cannot have layout qualifiers in GLSL,
+        // so order of the matrices is set for the whole block.
+        let qualifier = match Layout::ROW_MAJOR {
+            false => Layout::QUALIFIER.to_owned(),
+            true => format!("{}, row_major", Layout::QUALIFIER),
+        };
+        let mut definition = format!("layout({qualifier}) {storage} {name} {{\n");
+        for (field, ty) in T::layout_fields::<Layout>() {
+            definition.push_str("    ");
+            definition.push_str(&c_field_declaration(field, &ty));
+            definition.push_str(";\n");
+        }
+        match instance {
+            None => definition.push_str("};\n"),
+            Some(instance) => definition.push_str(&format!("}} {instance};\n")),
+        }
+        definition
+    }
+
+    /// `#extension` directive that enables extension required by the `Layout`, if any.
+    pub fn extension_directive<Layout>() -> Option<String>
+    where
+        Layout: GlslLayout,
+    {
+        Layout::EXTENSION.map(|extension| format!("#extension {extension} : require\n"))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl ShaderLang for Glsl {
+    #[inline(always)]
+    fn array_name(elem: &str, len: usize) -> String {
+        c_array_name(elem, len)
+    }
+
+    #[inline(always)]
+    fn struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String {
+        c_struct_definition(name, fields)
+    }
+
+    #[inline(always)]
+    fn constant_definition(name: &str, ty: &str, value: &str) -> String {
+        format!("const {ty} {name} = {value};\n")
+    }
+
+    #[inline(always)]
+    fn bitcast(ty: &str, word: &str) -> String {
+        match ty {
+            "float" => format!("uintBitsToFloat({word})"),
+            _ => format!("{ty}({word})"),
+        }
+    }
+
+    #[inline(always)]
+    fn unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String {
+        c_unpack_function(name, ty, result, fields)
+    }
+
+    #[inline(always)]
+    fn function_definition(
+        name: &str,
+        params: &[(&str, &str)],
+        result: &str,
+        expr: &str,
+    ) -> String {
+        c_function_definition(name, params, result, expr)
+    }
+}
+
 #[cfg(feature = "codegen")]
 codegen_builtin!(in Glsl => {
     vec2b as bvec2,
@@ -192,9 +415,34 @@ codegen_builtin!(in Glsl => {
     vec2f as vec2,
     vec3f as vec3,
     vec4f as vec4,
+    vec2i8 as i8vec2,
+    vec3i8 as i8vec3,
+    vec4i8 as i8vec4,
+    vec2u8 as u8vec2,
+    vec3u8 as u8vec3,
+    vec4u8 as u8vec4,
+    vec2i16 as i16vec2,
+    vec3i16 as i16vec3,
+    vec4i16 as i16vec4,
+    vec2u16 as u16vec2,
+    vec3u16 as u16vec3,
+    vec4u16 as u16vec4,
+    vec2i64 as i64vec2,
+    vec3i64 as i64vec3,
+    vec4i64 as i64vec4,
+    vec2u64 as u64vec2,
+    vec3u64 as u64vec3,
+    vec4u64 as u64vec4,
+    vec2h as f16vec2,
+    vec3h as f16vec3,
+    vec4h as f16vec4,
     vec2<f64> as dvec2,
     vec3<f64> as dvec3,
     vec4<f64> as dvec4,
+});
+
+#[cfg(feature = "codegen")]
+codegen_builtin!(in Glsl => matrices {
     mat2x2b as bmat2x2,
     mat2x3b as bmat2x3,
     mat2x4b as bmat2x4,
@@ -231,6 +479,15 @@ codegen_builtin!(in Glsl => {
     mat4x2f as mat4x2,
     mat4x3f as mat4x3,
     mat4x4f as mat4x4,
+    mat2x2h as f16mat2x2,
+    mat2x3h as f16mat2x3,
+    mat2x4h as f16mat2x4,
+    mat3x2h as f16mat3x2,
+    mat3x3h as f16mat3x3,
+    mat3x4h as f16mat3x4,
+    mat4x2h as f16mat4x2,
+    mat4x3h as f16mat4x3,
+    mat4x4h as f16mat4x4,
     mat2x2<f64> as dmat2x2,
     mat2x3<f64> as dmat2x3,
     mat2x4<f64> as dmat2x4,
@@ -241,3 +498,16 @@ codegen_builtin!(in Glsl => {
     mat4x3<f64> as dmat4x3,
     mat4x4<f64> as dmat4x4,
 });
+
+#[cfg(feature = "codegen")]
+codegen_packed!(in Glsl => {
+    Unorm8x4 as vec4f => "unpackUnorm4x8(value)",
+    Snorm8x4 as vec4f => "unpackSnorm4x8(value)",
+    Unorm16x2 as vec2f => "unpackUnorm2x16(value)",
+    Snorm16x2 as vec2f => "unpackSnorm2x16(value)",
+    Unorm10_10_10_2 as vec4f => "vec4((uvec4(value) >> uvec4(0u, 10u, 20u, 30u)) & uvec4(1023u, 1023u, 1023u, 3u)) / vec4(1023.0, 1023.0, 1023.0, 3.0)",
+    OctNormal as vec3f => "OctNormal_unfold(unpackSnorm2x16(value))"
+        where OctNormal_unfold(p: vec2f) => "normalize(vec3(p - sign(p) * max(abs(p.x) + abs(p.y) - 1.0, 0.0), 1.0 - abs(p.x) - abs(p.y)))",
+    Rgb9e5 as vec3f => "vec3((uvec3(value) >> uvec3(0u, 9u, 18u)) & uvec3(511u)) * exp2(float(value >> 27u) - 24.0)",
+    Rg11b10f as vec3f => "vec3(unpackHalf2x16(((value << 4u) & 0x7ff0u) | ((value << 9u) & 0x7ff00000u)), unpackHalf2x16((value >> 17u) & 0x7fe0u).x)",
+});
@@ -7,43 +7,259 @@ use crate::*;
 
 pub enum Hlsl {}
 
+/// Layout of `StructuredBuffer`, `RWStructuredBuffer` and `ByteAddressBuffer` loads.
+///
+/// It is C-like, every type is aligned as its scalar components
+/// and arrays are tightly packed.
+/// This is the default layout for HLSL.
+pub type HlslStructured = DefaultLayout;
+
+/// Packing rules of the HLSL constant buffers.
+///
+/// Constant buffer consists of 16-byte registers.
+/// Members may not cross register boundary, so a `float3` after `float2`
+/// starts in the next register.
+/// Arrays, matrices and structures start on register boundary.
+/// Each array element and each matrix vector takes its own register,
+/// but the last one is not padded and following members may be packed after it.
+/// Members that follow structures start in the next register.
+///
+/// Generic structures are laid out with Rust alignment in this layout,
+/// and fail to compile when used if that does not match the packing rules.
+pub enum HlslCBuffer {}
+
+impl HlslCBuffer {
+    /// Size of the register.
+    pub const REGISTER: usize = 16;
+
+    /// Places fields one after another using constant buffer packing rules.
+    ///
+    /// `end_aligns` are alignments of the offset that follows each field,
+    /// as `HlslCBufferRepr::END_ALIGN`.
+    /// Offsets of `fields` are replaced.
+    pub const fn place<const N: usize>(
+        mut fields: [FieldLayout; N],
+        aligns: &[usize; N],
+        end_aligns: &[usize; N],
+    ) -> [FieldLayout; N] {
+        let mut end = 0usize;
+        let mut idx = 0;
+        while idx < N {
+            fields[idx].offset = Self::offset(end, aligns[idx], fields[idx].size);
+            end = (fields[idx].offset + fields[idx].size).next_multiple_of(end_aligns[idx]);
+            idx += 1;
+        }
+        fields
+    }
+
+    /// Checks that fields are placed as by [`HlslCBuffer::place`].
+    pub const fn are_placed(
+        fields: &[FieldLayout],
+        aligns: &[usize],
+        end_aligns: &[usize],
+    ) -> bool {
+        if fields.len() != aligns.len() || fields.len() != end_aligns.len() {
+            return false;
+        }
+        let mut end = 0usize;
+        let mut idx = 0;
+        while idx < fields.len() {
+            if fields[idx].offset != Self::offset(end, aligns[idx], fields[idx].size) {
+                return false;
+            }
+            end = (fields[idx].offset + fields[idx].size).next_multiple_of(end_aligns[idx]);
+            idx += 1;
+        }
+        true
+    }
+
+    /// Offset of the field with given alignment and size placed after `end`.
+    ///
+    /// Field that would cross register boundary starts in the next register.
+    const fn offset(end: usize, align: usize, size: usize) -> usize {
+        let offset = end.next_multiple_of(align);
+        if offset % Self::REGISTER + size > Self::REGISTER {
+            offset.next_multiple_of(Self::REGISTER)
+        } else {
+            offset
+        }
+    }
+
+    /// Checks that no field crosses register boundary
+    /// unless it starts on register boundary.
+    pub const fn are_packed(fields: &[FieldLayout]) -> bool {
+        let mut idx = 0;
+        while idx < fields.len() {
+            let offset = fields[idx].offset % Self::REGISTER;
+            if offset != 0 && offset + fields[idx].size > Self::REGISTER {
+                return false;
+            }
+            idx += 1;
+        }
+        true
+    }
+}
+
+/// `HlslStructured` layout with row-major matrices.
+pub type HlslStructuredRowMajor = RowMajor<HlslStructured>;
+
+/// `HlslCBuffer` layout with row-major matrices.
+pub type HlslCBufferRowMajor = RowMajor<HlslCBuffer>;
+
+impl LayoutRules for HlslCBuffer {
+    const ALIGN16: bool = true;
+    const SUFFIX: &'static str = "_CBuffer";
+}
+
+/// Layout of the HLSL resources.
+pub trait HlslLayout: LayoutRules {
+    /// Matrix packing order for `#pragma pack_matrix`.
+    ///
+    /// HLSL type of `mat<T, N, M>` has `N` rows of `M` components.
+    /// So matrices stored as `N` vectors are declared `row_major`
+    /// and matrices transposed by `RowMajor` are declared `column_major`.
+    const PACK_MATRIX: &'static str;
+}
+
+impl HlslLayout for HlslStructured {
+    const PACK_MATRIX: &'static str = "row_major";
+}
+
+impl HlslLayout for HlslCBuffer {
+    const PACK_MATRIX: &'static str = "row_major";
+}
+
+impl<L> HlslLayout for RowMajor<L>
+where
+    L: HlslLayout,
+{
+    const PACK_MATRIX: &'static str = "column_major";
+}
+
+/// Trait for types that can be placed into constant buffer.
+pub trait HlslCBufferRepr<Layout = HlslCBuffer>: ShaderRepr<Hlsl, Layout> {
+    /// Size of the value in constant buffer.
+    ///
+    /// It may be less than the size of the repr-type,
+    /// because the last array element or matrix vector is not padded.
+    const SIZE: usize;
+
+    /// Alignment of the offset of the member that follows the value.
+    ///
+    /// Members after structures and arrays of structures start in the next register.
+    const END_ALIGN: usize = 1;
+}
+
+// `bool` occupies 4 bytes in HLSL buffers.
+shader_scalar!(in Hlsl as u32 => {
+    bool | x1 = Align4, x2 = Align8, x4 = Align16;
+});
+
+// `float16_t` and `int16_t` require `-enable-16bit-types`.
+// `min16float` is not used as it may take 4 bytes in buffers.
+// `int64_t` requires 64-bit integer support. HLSL has no 8-bit integers.
 shader_scalar!(in Hlsl => {
-    bool | x1 = Align1, x2 = Align2, x4 = Align4;
+    i16 as int16_t | x1 = Align2, x2 = Align4, x4 = Align8;
+    u16 as uint16_t | x1 = Align2, x2 = Align4, x4 = Align8;
     i32 as int | x1 = Align4, x2 = Align8, x4 = Align16;
     u32 as uint | x1 = Align4, x2 = Align8, x4 = Align16;
+    i64 as int64_t | x1 = Align8, x2 = Align16, x4 = Align32;
+    u64 as uint64_t | x1 = Align8, x2 = Align16, x4 = Align32;
+    f16 as float16_t | x1 = Align2, x2 = Align4, x4 = Align8;
     f32 as float | x1 = Align4, x2 = Align8, x4 = Align16;
     f64 as double | x1 = Align8, x2 = Align16, x4 = Align32;
 });
 
-macro_rules! vec_mat_repr {
+macro_rules! cbuffer_scalar {
     ($($t:ty)+) => {$(
-        impl<const N: usize> ShaderRepr<Hlsl> for vec<$t, N> {
+        impl HlslCBufferRepr for $t {
+            const SIZE: usize = size_of::<<$t as ShaderRepr<Hlsl>>::Repr>();
+        }
+
+        impl HlslCBufferRepr<HlslCBufferRowMajor> for $t {
+            const SIZE: usize = size_of::<<$t as ShaderRepr<Hlsl>>::Repr>();
+        }
+    )+};
+}
+
+cbuffer_scalar! { bool i16 u16 i32 u32 i64 u64 f16 f32 f64 }
+cbuffer_scalar! { Unorm8x4 Snorm8x4 Unorm16x2 Snorm16x2 Unorm10_10_10_2 OctNormal Rgb9e5 Rg11b10f }
+
+macro_rules! vec_repr {
+    ($($t:ty)+) => {$(
+        impl<const N: usize> ShaderRepr<Hlsl, HlslStructured> for vec<$t, N> {
             type Align = align!(in Hlsl, $t);
-            type Repr = Self;
+            type Repr = vec<<$t as ShaderRepr<Hlsl>>::Repr, N>;
 
             #[inline(always)]
-            fn repr(&self) -> Self {
-                *self
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Hlsl>>::repr(&c)))
             }
         }
 
-        impl<const N: usize, const M: usize> ShaderRepr<Hlsl> for mat<$t, N, M> {
+        impl<const N: usize> ShaderRepr<Hlsl, HlslCBuffer> for vec<$t, N> {
+            type Align = align!(in Hlsl as HlslCBuffer, $t);
+            type Repr = vec<<$t as ShaderRepr<Hlsl>>::Repr, N>;
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                vec(self.0.map(|c| <$t as ShaderRepr<Hlsl>>::repr(&c)))
+            }
+        }
+
+        impl<const N: usize> HlslCBufferRepr for vec<$t, N> {
+            const SIZE: usize = size_of::<Self::Repr>();
+        }
+
+        impl<const N: usize> HlslCBufferRepr<HlslCBufferRowMajor> for vec<$t, N> {
+            const SIZE: usize = size_of::<<Self as ShaderRepr<Hlsl, HlslCBuffer>>::Repr>();
+        }
+    )*};
+}
+
+vec_repr! { bool i16 u16 i32 u32 i64 u64 f16 f32 f64 }
+
+macro_rules! mat_repr {
+    ($($t:ty)+) => {$(
+        impl<const N: usize, const M: usize> ShaderRepr<Hlsl, HlslStructured> for mat<$t, N, M> {
             type Align = align!(in Hlsl, $t);
-            type Repr = Self;
+            type Repr = mat<<$t as ShaderRepr<Hlsl>>::Repr, N, M>;
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                *self
+                mat(self.0.map(|v| v.map(|c| <$t as ShaderRepr<Hlsl>>::repr(&c))))
             }
         }
+
+        /// Each vector of the matrix starts in a new register.
+        impl<const N: usize, const M: usize> ShaderRepr<Hlsl, HlslCBuffer> for mat<$t, N, M> {
+            type Align = (Align16, align!(in Hlsl as HlslCBuffer, $t));
+            type Repr = [Aligned<Self::Align, vec<<$t as ShaderRepr<Hlsl>>::Repr, M>>; N];
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                self.0.map(|v| Aligned::new(vec(v.map(|c| <$t as ShaderRepr<Hlsl>>::repr(&c)))))
+            }
+        }
+
+        impl<const N: usize, const M: usize> HlslCBufferRepr for mat<$t, N, M> {
+            const SIZE: usize = cbuffer_array_size::<Aligned<Align16, vec<<$t as ShaderRepr<Hlsl>>::Repr, M>>>(
+                N,
+                size_of::<vec<<$t as ShaderRepr<Hlsl>>::Repr, M>>(),
+            );
+        }
+
+        impl<const N: usize, const M: usize> HlslCBufferRepr<HlslCBufferRowMajor> for mat<$t, N, M> {
+            const SIZE: usize = <mat<$t, M, N> as HlslCBufferRepr>::SIZE;
+        }
     )*};
 }
 
-vec_mat_repr! { bool i32 u32 f32 f64 }
+mat_repr! { bool i32 u32 f16 f32 f64 }
 
-impl<T, const N: usize> ShaderRepr<Hlsl> for [T; N]
+impl<T, const N: usize> ShaderRepr<Hlsl, HlslStructured> for [T; N]
 where
-    T: ShaderRepr<Hlsl>,
+    T: ShaderRepr<Hlsl, HlslStructured>,
 {
     type Align = T::Align;
     type Repr = [Aligned<Self::Align, T::Repr>; N];
@@ -68,6 +284,122 @@ where
     }
 }
 
+/// Size of the array in constant buffer.
+///
+/// Every element is padded to `E` except the last one.
+const fn cbuffer_array_size<E>(len: usize, size: usize) -> usize {
+    match len {
+        0 => 0,
+        _ => size_of::<E>() * (len - 1) + size,
+    }
+}
+
+/// Each element of the array starts in a new register.
+impl<T, const N: usize> ShaderRepr<Hlsl, HlslCBuffer> for [T; N]
+where
+    T: HlslCBufferRepr,
+{
+    type Align = (Align16, T::Align);
+    type Repr = [Aligned<Self::Align, T::Repr>; N];
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        // SAFETY: Array of `MaybeUninit` is always initialized.
+        let mut array: [MaybeUninit<_>; N] = unsafe { MaybeUninit::uninit().assume_init() };
+        for (i, elem) in self.iter().enumerate() {
+            array[i] = MaybeUninit::new(Aligned::new(elem.repr()));
+        }
+
+        // SAFETY: Every element of `array` was initialized.
+        array.map(|elem| unsafe { MaybeUninit::assume_init(elem) })
+    }
+}
+
+impl<T, const N: usize> HlslCBufferRepr for [T; N]
+where
+    T: HlslCBufferRepr,
+{
+    const SIZE: usize = cbuffer_array_size::<Aligned<Self::Align, T::Repr>>(N, T::SIZE);
+    const END_ALIGN: usize = T::END_ALIGN;
+}
+
+impl<T> HlslCBufferRepr for RowMajorElem<T>
+where
+    T: HlslCBufferRepr<HlslCBufferRowMajor>,
+{
+    const SIZE: usize = T::SIZE;
+    const END_ALIGN: usize = T::END_ALIGN;
+}
+
+impl<T, const N: usize> HlslCBufferRepr<HlslCBufferRowMajor> for [T; N]
+where
+    T: HlslCBufferRepr<HlslCBufferRowMajor>,
+{
+    const SIZE: usize = <[RowMajorElem<T>; N] as HlslCBufferRepr>::SIZE;
+    const END_ALIGN: usize = T::END_ALIGN;
+}
+
+#[cfg(feature = "codegen")]
+impl Hlsl {
+    /// Pragma that sets matrix packing order matching the `Layout`.
+    ///
+    /// Matrix members of the structures are declared with qualifiers of their layout,
+    /// so the pragma only affects matrices declared outside of them.
+    pub fn pack_matrix_pragma<Layout>() -> String
+    where
+        Layout: HlslLayout,
+    {
+        format!("#pragma pack_matrix({})\n", Layout::PACK_MATRIX)
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl ShaderLang for Hlsl {
+    #[inline(always)]
+    fn array_name(elem: &str, len: usize) -> String {
+        c_array_name(elem, len)
+    }
+
+    #[inline(always)]
+    fn struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String {
+        c_struct_definition(name, fields)
+    }
+
+    #[inline(always)]
+    fn matrix_member_name(ty: &str, row_major: bool) -> String {
+        // HLSL type of `mat<T, N, M>` has `N` rows, see `HlslLayout::PACK_MATRIX`.
+        match row_major {
+            false => format!("row_major {ty}"),
+            true => format!("column_major {ty}"),
+        }
+    }
+
+    #[inline(always)]
+    fn constant_definition(name: &str, ty: &str, value: &str) -> String {
+        format!("static const {ty} {name} = {value};\n")
+    }
+
+    #[inline(always)]
+    fn bitcast(ty: &str, word: &str) -> String {
+        format!("as{ty}({word})")
+    }
+
+    #[inline(always)]
+    fn unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String {
+        c_unpack_function(name, ty, result, fields)
+    }
+
+    #[inline(always)]
+    fn function_definition(
+        name: &str,
+        params: &[(&str, &str)],
+        result: &str,
+        expr: &str,
+    ) -> String {
+        c_function_definition(name, params, result, expr)
+    }
+}
+
 #[cfg(feature = "codegen")]
 codegen_builtin!(in Hlsl => {
     vec2b as bool2,
@@ -79,12 +411,31 @@ codegen_builtin!(in Hlsl => {
     vec2<u32> as uint2,
     vec3<u32> as uint3,
     vec4<u32> as uint4,
+    vec2i16 as int16_t2,
+    vec3i16 as int16_t3,
+    vec4i16 as int16_t4,
+    vec2u16 as uint16_t2,
+    vec3u16 as uint16_t3,
+    vec4u16 as uint16_t4,
+    vec2i64 as int64_t2,
+    vec3i64 as int64_t3,
+    vec4i64 as int64_t4,
+    vec2u64 as uint64_t2,
+    vec3u64 as uint64_t3,
+    vec4u64 as uint64_t4,
     vec2<f32> as float2,
     vec3<f32> as float3,
     vec4<f32> as float4,
+    vec2<f16> as float16_t2,
+    vec3<f16> as float16_t3,
+    vec4<f16> as float16_t4,
     vec2<f64> as double2,
     vec3<f64> as double3,
     vec4<f64> as double4,
+});
+
+#[cfg(feature = "codegen")]
+codegen_builtin!(in Hlsl => matrices {
     mat2x2<bool> as bool2x2,
     mat2x3<bool> as bool2x3,
     mat2x4<bool> as bool2x4,
@@ -121,6 +472,15 @@ codegen_builtin!(in Hlsl => {
     mat4x2<f32> as float4x2,
     mat4x3<f32> as float4x3,
     mat4x4<f32> as float4x4,
+    mat2x2<f16> as float16_t2x2,
+    mat2x3<f16> as float16_t2x3,
+    mat2x4<f16> as float16_t2x4,
+    mat3x2<f16> as float16_t3x2,
+    mat3x3<f16> as float16_t3x3,
+    mat3x4<f16> as float16_t3x4,
+    mat4x2<f16> as float16_t4x2,
+    mat4x3<f16> as float16_t4x3,
+    mat4x4<f16> as float16_t4x4,
     mat2x2<f64> as double2x2,
     mat2x3<f64> as double2x3,
     mat2x4<f64> as double2x4,
@@ -131,3 +491,17 @@ codegen_builtin!(in Hlsl => {
     mat4x3<f64> as double4x3,
     mat4x4<f64> as double4x4,
 });
+
+// HLSL has no unpacking intrinsics, normalized values are unpacked with bit operations.
+#[cfg(feature = "codegen")]
+codegen_packed!(in Hlsl => {
+    Unorm8x4 as vec4f => "float4((value >> uint4(0, 8, 16, 24)) & 255) / 255.0",
+    Snorm8x4 as vec4f => "max(float4(asint(value << uint4(24, 16, 8, 0)) >> 24) / 127.0, -1.0)",
+    Unorm16x2 as vec2f => "float2((value >> uint2(0, 16)) & 65535) / 65535.0",
+    Snorm16x2 as vec2f => "max(float2(asint(value << uint2(16, 0)) >> 16) / 32767.0, -1.0)",
+    Unorm10_10_10_2 as vec4f => "float4((value >> uint4(0, 10, 20, 30)) & uint4(1023, 1023, 1023, 3)) / float4(1023.0, 1023.0, 1023.0, 3.0)",
+    OctNormal as vec3f => "OctNormal_unfold(max(float2(asint(value << uint2(16, 0)) >> 16) / 32767.0, -1.0))"
+        where OctNormal_unfold(p: vec2f) => "normalize(float3(p - sign(p) * max(abs(p.x) + abs(p.y) - 1.0, 0.0), 1.0 - abs(p.x) - abs(p.y)))",
+    Rgb9e5 as vec3f => "float3((value >> uint3(0, 9, 18)) & 511) * exp2(float(value >> 27) - 24.0)",
+    Rg11b10f as vec3f => "f16tof32(uint3((value << 4) & 0x7ff0, (value >> 7) & 0x7ff0, (value >> 17) & 0x7fe0))",
+});
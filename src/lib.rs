@@ -3,7 +3,7 @@
 //!
 //! The primary trait is `ShaderRepr` which is implemented for
 //! fundamental types that can be sent to the shaders.
-//! And derive macro to implement it for user-defined structures and enums (with only unit variants).
+//! And derive macro to implement it for user-defined structures and enums.
 #![cfg_attr(not(feature = "codegen"), no_std)]
 
 #[macro_export]
@@ -20,17 +20,23 @@ macro_rules! ident_or_ident {
 ///
 /// This implements `ShaderRepr` and `ShaderScalar` for the type.
 /// And if `codegen` feature is enabled it also implements `CodeGen`.
+///
+/// With `in Lang as Model` the scalar is represented by `Model` in the language,
+/// for example `bool` stored as `u32`.
 #[macro_export]
 macro_rules! shader_scalar {
     (in $lang:ident => $t:ident $(as $st:ident)? | x1 = $align:ident, x2 = $align_x2:ident, x4 = $align_x4:ident) => {
+        shader_scalar!(in $lang as $t => $t $(as $st)? | x1 = $align, x2 = $align_x2, x4 = $align_x4);
+    };
+    (in $lang:ident as $model:ident => $t:ident $(as $st:ident)? | x1 = $align:ident, x2 = $align_x2:ident, x4 = $align_x4:ident) => {
         /// Repr is the same in all layouts for scalars.
         impl<Layout> $crate::ShaderRepr<$lang, Layout> for $t {
             type Align = $align;
-            type Repr = Self;
+            type Repr = $model;
 
             #[inline(always)]
             fn repr(&self) -> Self::Repr {
-                *self as _
+                *self as $model
             }
         }
 
@@ -112,26 +118,181 @@ macro_rules! codegen_builtin {
     (in $lang:ident => { $($t:ty $(as $st:ident)?),+ $(,)? }) => {
         $(codegen_builtin!(in $lang => $t $(as $st)?);)+
     };
+    (in $lang:ident => matrices { $($t:ty $(as $st:ident)?),+ $(,)? }) => {$(
+        impl CodeGen<$lang> for $t {
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                // Built-in types
+                ::std::borrow::Cow::Borrowed({::core::stringify!($t) $(;::core::stringify!($st))?})
+            }
+
+            #[inline(always)]
+            fn definition() -> ::std::option::Option<::std::string::String> {
+                // Built-in types need no definition.
+                ::std::option::Option::None
+            }
+
+            #[inline(always)]
+            fn member_name<Layout: LayoutRules>() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Owned(<$lang as ShaderLang>::matrix_member_name(
+                    &<Self as CodeGen<$lang>>::name(),
+                    Layout::ROW_MAJOR,
+                ))
+            }
+        }
+    )+};
+}
+
+/// Implement `CodeGen` for the packed types.
+///
+/// Packed value is declared as unsigned integer.
+/// Definition is a function `{Name}_unpack` that unpacks it
+/// with the expression of the function argument named `value`.
+/// Structures with fields of packed types define accessors that call it.
+///
+/// With `where helper(param: T) => "expr"` a helper function returning the same type
+/// is defined before it, so that the unpacking expression may call it.
+#[cfg(feature = "codegen")]
+#[macro_export]
+macro_rules! codegen_packed {
+    (in $lang:ident => { $(
+        $t:ident as $v:ty => $expr:literal
+        $(where $helper:ident($param:ident: $pt:ty) => $helper_expr:literal)?
+    ),+ $(,)? }) => {$(
+        impl CodeGen<$lang> for $t {
+            #[inline(always)]
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                <u32 as CodeGen<$lang>>::name()
+            }
+
+            fn definition() -> ::std::option::Option<::std::string::String> {
+                let mut definition = ::std::string::String::new();
+                $(
+                    definition.push_str(&<$lang as ShaderLang>::function_definition(
+                        ::core::stringify!($helper),
+                        &[(::core::stringify!($param), &<$pt as CodeGen<$lang>>::name())],
+                        &<$v as CodeGen<$lang>>::name(),
+                        $helper_expr,
+                    ));
+                )?
+                definition.push_str(&<$lang as ShaderLang>::function_definition(
+                    ::core::concat!(::core::stringify!($t), "_unpack"),
+                    &[("value", &<u32 as CodeGen<$lang>>::name())],
+                    &<$v as CodeGen<$lang>>::name(),
+                    $expr,
+                ));
+                ::std::option::Option::Some(definition)
+            }
+
+            #[inline(always)]
+            fn unpack() -> ::std::option::Option<(
+                ::std::borrow::Cow<'static, str>,
+                ::std::borrow::Cow<'static, str>,
+            )> {
+                ::std::option::Option::Some((
+                    ::std::borrow::Cow::Borrowed(::core::concat!(::core::stringify!($t), "_unpack")),
+                    <$v as CodeGen<$lang>>::name(),
+                ))
+            }
+        }
+    )+};
 }
 
+mod packed;
+mod row_major;
 mod types;
 
-pub use self::types::*;
+pub use self::{packed::*, row_major::*, types::*};
+
+/// Derive macro for `ShaderRepr`.
+///
+/// Generates a hidden repr-type for each enabled shader language and layout
+/// where every field is aligned as required by the layout.
+/// Field may be placed into the padding at the end of the previous field
+/// when the layout allows it, like a `float` right after `vec3`.
+///
+/// Fields can be configured with `#[shader(...)]` attributes:
+///
+/// * `rename = "name"` - name of the field in the shader.
+/// * `skip` - field is not sent to the shader.
+/// * `as = T` - field is converted into `T` with `From` and laid out as `T`.
+/// * `flatten` - fields of the nested structure are inlined into this structure.
+///   They are laid out by the rules for this structure, as if declared in place of the field.
+///   Structures with flattened fields cannot be generic.
+///
+/// Generated shader code contains `{Name}_{field}` functions
+/// that unpack fields of packed types, like `Unorm8x4`.
+///
+/// Structures can be marked with `#[shader(stride_align = N)]`
+/// to align them to `N` bytes and round their size up to `N` in every layout,
+/// so that consecutive values can be bound with dynamic offsets.
+/// `N` must be a power of two up to 256.
+///
+/// Structures with only `bool` fields can be marked with `#[shader(bitflags)]`.
+/// They are represented as `u32` mask with one bit per field in order of declaration.
+/// Generated shader code contains constants for the bits
+/// and `{Name}_has_flag(flags, flag)` function to test them.
+///
+/// Enums with only unit variants are represented as integers.
+/// Enums with data in variants must be marked with `#[shader(tagged)]`.
+/// They are represented as `u32` tag with index of the variant
+/// followed by payload that fits any variant and is aligned to at least 16 bytes.
+/// Variants are laid out with each layout of the language.
+/// Enums without data in any variant have no payload.
+/// Generated shader code contains tag constants, a structure for each variant
+/// and functions to unpack variants from the payload:
+/// `{Name}_as_{Variant}` for the default layout of the language
+/// and `{Name}_as_{Variant}_{Layout}` for other layouts.
+/// With code-generation fields of the variants must implement `ShaderLoad`
+/// or be arrays of such types.
+pub use shader_data_proc::ShaderRepr;
 
 #[cfg(feature = "glsl")]
 mod glsl;
 
+#[cfg(feature = "glsl")]
+pub use self::glsl::*;
+
 #[cfg(feature = "wgsl")]
 mod wgsl;
 
+#[cfg(feature = "wgsl")]
+pub use self::wgsl::*;
+
 #[cfg(feature = "hlsl")]
 mod hlsl;
 
+#[cfg(feature = "hlsl")]
+pub use self::hlsl::*;
+
 #[cfg(feature = "msl")]
 mod msl;
 
+#[cfg(feature = "msl")]
+pub use self::msl::*;
+
 pub enum DefaultLayout {}
 
+/// Rules of the layout that code-generation declares explicitly.
+///
+/// Code-generation declares matrix members of the structures with qualifiers of the layout,
+/// so that definitions do not depend on defaults of the shader.
+/// Languages that do not pad values as the layout requires get the padding declared.
+pub trait LayoutRules {
+    /// Matrices are stored in row-major order.
+    const ROW_MAJOR: bool = false;
+
+    /// Array elements and structures are aligned to 16 bytes,
+    /// unlike in the default layout of the language.
+    const ALIGN16: bool = false;
+
+    /// Suffix of the names of the structures declared differently than in the default layout.
+    const SUFFIX: &'static str;
+}
+
+impl LayoutRules for DefaultLayout {
+    const SUFFIX: &'static str = "";
+}
+
 /// Trait for types that can be sent to the shaders.
 /// It provides repr-types for different shader languages (like GLSL, HLSL, etc.)
 /// and methods to create repr-values from `&self`.
@@ -153,8 +314,10 @@ pub trait ShaderRepr<Lang, Layout = DefaultLayout>: 'static {
 }
 
 /// Trait for scalar types in the shader languages.
-/// They must be represented by self in the shader languages in any layout.
-pub trait ShaderScalar<Lang>: ShaderRepr<Lang, Repr = Self> + Copy {
+/// They must have the same representation in the shader languages in any layout.
+///
+/// Vectors and matrices are represented by vectors and matrices of scalar repr-types.
+pub trait ShaderScalar<Lang>: ShaderRepr<Lang> + Copy {
     /// Doubled alignment ZST for the type.
     type AlignX2: Default + Copy + 'static;
 
@@ -162,6 +325,186 @@ pub trait ShaderScalar<Lang>: ShaderRepr<Lang, Repr = Self> + Copy {
     type AlignX4: Default + Copy + 'static;
 }
 
+/// Trait for structures with known layout of their fields in the shader.
+///
+/// Implemented by derive macro along with `ShaderRepr`.
+pub trait ShaderFields<Lang, Layout = DefaultLayout>: ShaderRepr<Lang, Layout> {
+    /// Layout of the fields in the repr-type in declaration order.
+    const FIELDS: &'static [FieldLayout];
+}
+
+/// Trait for structures which fields can be inlined into another structure
+/// with `#[shader(flatten)]` attribute.
+///
+/// Implemented by derive macro along with `ShaderRepr`.
+pub trait ShaderFlatten<Lang, Layout = DefaultLayout>: ShaderFields<Lang, Layout> {
+    /// Alignment ZST of the most aligned field.
+    ///
+    /// Unlike `ShaderRepr::Align` it is not affected by layout rules for structures.
+    type Align: Default + Copy + 'static;
+
+    /// Alignment of each field in the same order as `ShaderFields::FIELDS`.
+    const ALIGNS: &'static [usize];
+
+    /// Alignment of the offset that follows each field in the same order as `ShaderFields::FIELDS`.
+    ///
+    /// It is more than 1 only in layouts where members after structures
+    /// start in a new slot, like `HlslCBuffer`.
+    const END_ALIGNS: &'static [usize];
+
+    /// Writes repr-value of each field into `bytes`.
+    ///
+    /// `fields` are layouts of the fields in the outer structure
+    /// in the same order as `ShaderFields::FIELDS`.
+    fn write_fields(&self, bytes: &mut [core::mem::MaybeUninit<u8>], fields: &[FieldLayout]);
+}
+
+/// Layout of the structure field in the shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// Name of the field in the shader.
+    pub name: &'static str,
+
+    /// Offset of the field from the start of the structure.
+    pub offset: usize,
+
+    /// Size of the field.
+    pub size: usize,
+}
+
+impl FieldLayout {
+    /// Returns offset of the field with given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no field with given name.
+    /// When evaluated in const context this results in compile-time error.
+    pub const fn offset_of(fields: &[FieldLayout], name: &str) -> usize {
+        let mut idx = 0;
+        while idx < fields.len() {
+            if str_eq(fields[idx].name, name) {
+                return fields[idx].offset;
+            }
+            idx += 1;
+        }
+        panic!("no field with such name")
+    }
+
+    /// Checks that fields are placed in declaration order and do not overlap.
+    pub const fn are_ordered(fields: &[FieldLayout]) -> bool {
+        let mut idx = 1;
+        while idx < fields.len() {
+            if fields[idx - 1].offset + fields[idx - 1].size > fields[idx].offset {
+                return false;
+            }
+            idx += 1;
+        }
+        true
+    }
+
+    /// Checks that offset of each field is a multiple of its alignment.
+    pub const fn are_aligned(fields: &[FieldLayout], aligns: &[usize]) -> bool {
+        if fields.len() != aligns.len() {
+            return false;
+        }
+        let mut idx = 0;
+        while idx < fields.len() {
+            if !fields[idx].offset.is_multiple_of(aligns[idx]) {
+                return false;
+            }
+            idx += 1;
+        }
+        true
+    }
+
+    /// Checks that fields are placed as by [`FieldLayout::place`],
+    /// each at the first offset after the previous field that is a multiple of its alignment.
+    pub const fn are_placed(fields: &[FieldLayout], aligns: &[usize]) -> bool {
+        if fields.len() != aligns.len() {
+            return false;
+        }
+        let mut end = 0usize;
+        let mut idx = 0;
+        while idx < fields.len() {
+            if fields[idx].offset != end.next_multiple_of(aligns[idx]) {
+                return false;
+            }
+            end = fields[idx].offset + fields[idx].size;
+            idx += 1;
+        }
+        true
+    }
+
+    /// Returns size of the structure with given fields and alignment.
+    ///
+    /// It is the end of the last field rounded up to the alignment.
+    pub const fn struct_size(fields: &[FieldLayout], align: usize) -> usize {
+        let end = match fields.last() {
+            None => 0,
+            Some(last) => last.offset + last.size,
+        };
+        end.next_multiple_of(align)
+    }
+
+    /// Places fields one after another, each at offset that is a multiple of its alignment.
+    ///
+    /// Offsets of `fields` are replaced.
+    pub const fn place<const N: usize>(
+        mut fields: [FieldLayout; N],
+        aligns: &[usize; N],
+    ) -> [FieldLayout; N] {
+        let mut end = 0usize;
+        let mut idx = 0;
+        while idx < N {
+            fields[idx].offset = end.next_multiple_of(aligns[idx]);
+            end = fields[idx].offset + fields[idx].size;
+            idx += 1;
+        }
+        fields
+    }
+
+    /// Writes `value` into `bytes` at the offset of the field.
+    ///
+    /// Only first `size` bytes of the `value` are written.
+    /// Value may be larger than the field when its trailing padding
+    /// is used by the following fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is smaller than the field
+    /// or the field does not fit into `bytes`.
+    #[inline(always)]
+    pub fn write<T: Copy>(&self, bytes: &mut [core::mem::MaybeUninit<u8>], value: T) {
+        assert!(core::mem::size_of::<T>() >= self.size);
+        let bytes = &mut bytes[self.offset..][..self.size];
+
+        // Safety: `value` has at least `bytes.len()` bytes
+        // and `MaybeUninit<u8>` can hold any byte including padding.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (&value as *const T).cast::<core::mem::MaybeUninit<u8>>(),
+                bytes.as_mut_ptr(),
+                bytes.len(),
+            )
+        }
+    }
+}
+
+const fn str_eq(lhs: &str, rhs: &str) -> bool {
+    let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < lhs.len() {
+        if lhs[idx] != rhs[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
 /// ZST with alignment of 1.
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(C, align(1))]
@@ -192,6 +535,21 @@ pub struct Align16;
 #[repr(C, align(32))]
 pub struct Align32;
 
+/// ZST with alignment of 64.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C, align(64))]
+pub struct Align64;
+
+/// ZST with alignment of 128.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C, align(128))]
+pub struct Align128;
+
+/// ZST with alignment of 256.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C, align(256))]
+pub struct Align256;
+
 /// Combines alignment and type.
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
@@ -227,4 +585,322 @@ pub trait CodeGen<Lang> {
     /// When using code-generation returned string will be
     /// added once to the generated code if some.
     fn definition() -> Option<String>;
+
+    /// Name of the type laid out with `Layout`.
+    ///
+    /// Structures declared differently than in the default layout
+    /// have separate definitions named with `LayoutRules::SUFFIX`.
+    #[inline(always)]
+    fn layout_name<Layout: LayoutRules>() -> std::borrow::Cow<'static, str> {
+        Self::name()
+    }
+
+    /// Definition of the type laid out with `Layout`.
+    #[inline(always)]
+    fn layout_definition<Layout: LayoutRules>() -> Option<String> {
+        Self::definition()
+    }
+
+    /// Name of the type in member declarations of the structures laid out with `Layout`,
+    /// including qualifiers of the matrices.
+    #[inline(always)]
+    fn member_name<Layout: LayoutRules>() -> std::borrow::Cow<'static, str> {
+        Self::layout_name::<Layout>()
+    }
+
+    /// Name of the function that unpacks values of the type and name of the unpacked type,
+    /// if the type is packed.
+    ///
+    /// Structures define accessors that unpack their fields of such types.
+    #[inline(always)]
+    fn unpack() -> Option<(
+        std::borrow::Cow<'static, str>,
+        std::borrow::Cow<'static, str>,
+    )> {
+        None
+    }
+}
+
+/// Trait for structures which fields can be inlined into another structure
+/// with `#[shader(flatten)]` attribute.
+///
+/// Implemented by derive macro along with `CodeGen`.
+#[cfg(feature = "codegen")]
+pub trait CodeGenFields<Lang>: CodeGen<Lang> {
+    /// Pairs of field name and field type name in declaration order.
+    fn fields() -> Vec<(&'static str, std::borrow::Cow<'static, str>)>;
+
+    /// Pairs of field name and field type name with qualifiers
+    /// of the structure laid out with `Layout` in declaration order.
+    fn layout_fields<Layout: LayoutRules>() -> Vec<(&'static str, std::borrow::Cow<'static, str>)>;
+
+    /// Fields of packed types in declaration order,
+    /// with names of the functions that unpack them and of the unpacked types.
+    #[allow(clippy::type_complexity)]
+    fn packed_fields() -> Vec<(
+        &'static str,
+        std::borrow::Cow<'static, str>,
+        std::borrow::Cow<'static, str>,
+    )>;
+}
+
+/// Syntax of the shader language used by code-generation.
+#[cfg(feature = "codegen")]
+pub trait ShaderLang {
+    /// Name of the array type with `len` elements of type `elem`.
+    fn array_name(elem: &str, len: usize) -> String;
+
+    /// Definition of the structure with given fields.
+    ///
+    /// Fields are pairs of field name and field type name.
+    fn struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String;
+
+    /// Definition of the structure in the layout that aligns structures to 16 bytes if `align16`.
+    ///
+    /// Languages that align structures as the layout requires return the plain definition.
+    #[inline(always)]
+    fn layout_struct_definition(
+        name: &str,
+        fields: &[(&str, std::borrow::Cow<'static, str>)],
+        align16: bool,
+    ) -> String {
+        let _ = align16;
+        Self::struct_definition(name, fields)
+    }
+
+    /// Name of the array element type `ty`
+    /// in the layout that aligns array elements to 16 bytes if `align16`.
+    ///
+    /// Languages that pad array elements as the layout requires return `ty` as is.
+    #[inline(always)]
+    fn element_name(ty: &str, align16: bool) -> String {
+        let _ = align16;
+        ty.to_owned()
+    }
+
+    /// Name of the matrix type `ty` with qualifier of the matrix order
+    /// in member declarations of the structures.
+    ///
+    /// Languages that have no such qualifiers for structure members return `ty` as is.
+    #[inline(always)]
+    fn matrix_member_name(ty: &str, row_major: bool) -> String {
+        let _ = row_major;
+        ty.to_owned()
+    }
+
+    /// Definition of the named constant of type `ty`.
+    fn constant_definition(name: &str, ty: &str, value: &str) -> String;
+
+    /// Literal of unsigned integer type.
+    #[inline(always)]
+    fn uint_literal(value: u32) -> String {
+        format!("{value}u")
+    }
+
+    /// Literal of signed integer type.
+    #[inline(always)]
+    fn int_literal(value: i32) -> String {
+        format!("{value}")
+    }
+
+    /// Expression that reinterprets bits of unsigned 32-bit integer `word` as scalar type `ty`.
+    fn bitcast(ty: &str, word: &str) -> String;
+
+    /// Definition of the function that unpacks value of type `ty` into type `result`.
+    ///
+    /// Fields are pairs of result field name and expression that computes it
+    /// from the function argument named `value`.
+    fn unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String;
+
+    /// Definition of the function that returns value of expression `expr`.
+    ///
+    /// Parameters are pairs of parameter name and parameter type name.
+    fn function_definition(name: &str, params: &[(&str, &str)], result: &str, expr: &str)
+        -> String;
+}
+
+/// Trait for types that can be loaded from 32-bit words in the shader.
+///
+/// Code-generation uses it to unpack payloads of tagged enums.
+/// It is implemented for 32-bit scalars and their vectors,
+/// enums, bitflags and packed types.
+/// Arrays of these types are unpacked element by element.
+/// Matrices, structures, `bool` and scalars of other sizes
+/// cannot be fields of tagged enum variants when code-generation is enabled.
+#[cfg(feature = "codegen")]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be unpacked from payload of tagged enum in {Lang}",
+    note = "only 32-bit scalars and their vectors, enums, bitflags, packed types and arrays of them can be unpacked"
+)]
+pub trait ShaderLoad<Lang>: CodeGen<Lang> {
+    /// Expression that loads the value starting at word with index `offset`.
+    ///
+    /// `word` returns expression that reads unsigned 32-bit word with given index.
+    fn load(word: &dyn Fn(usize) -> String, offset: usize) -> String;
+}
+
+#[cfg(feature = "codegen")]
+macro_rules! shader_load_scalar {
+    ($($t:ty)*) => {$(
+        impl<Lang> ShaderLoad<Lang> for $t
+        where
+            Lang: ShaderLang,
+            $t: CodeGen<Lang>,
+        {
+            #[inline(always)]
+            fn load(word: &dyn Fn(usize) -> String, offset: usize) -> String {
+                Lang::bitcast(&Self::name(), &word(offset))
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "codegen")]
+shader_load_scalar!(i32 u32 f32);
+
+#[cfg(feature = "codegen")]
+impl<Lang, T, const N: usize> ShaderLoad<Lang> for vec<T, N>
+where
+    Lang: ShaderLang,
+    T: ShaderLoad<Lang>,
+    vec<T, N>: CodeGen<Lang>,
+{
+    fn load(word: &dyn Fn(usize) -> String, offset: usize) -> String {
+        // Only 32-bit scalars can be loaded, so each component takes one word.
+        let components = (0..N)
+            .map(|idx| T::load(word, offset + idx))
+            .collect::<Vec<_>>();
+        format!("{}({})", Self::name(), components.join(", "))
+    }
+}
+
+/// Name of the generic type instantiation in the shader languages.
+///
+/// Shader names of generic arguments are appended to the type name
+/// with every non-alphanumeric character replaced by underscore.
+/// For example `Light<f32>` becomes `Light_float` in GLSL, HLSL and MSL
+/// and `Light_f32` in WGSL, while `Bounds<vec3<f32>>` becomes
+/// `Bounds_vec3` in GLSL, `Bounds_float3` in HLSL and MSL
+/// and `Bounds_vec3_f32` in WGSL.
+#[cfg(feature = "codegen")]
+pub fn mangled_name(name: &str, args: &[std::borrow::Cow<'_, str>]) -> String {
+    let mut mangled = String::from(name);
+    for arg in args {
+        mangled.push('_');
+        for part in arg
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .filter(|part| !part.is_empty())
+        {
+            if !mangled.ends_with('_') {
+                mangled.push('_');
+            }
+            mangled.push_str(part);
+        }
+    }
+    mangled
+}
+
+#[cfg(feature = "codegen")]
+impl<Lang, T, const N: usize> CodeGen<Lang> for [T; N]
+where
+    Lang: ShaderLang,
+    T: CodeGen<Lang>,
+{
+    #[inline(always)]
+    fn name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Owned(Lang::array_name(&T::name(), N))
+    }
+
+    #[inline(always)]
+    fn definition() -> Option<String> {
+        // Arrays need no definition.
+        None
+    }
+
+    #[inline(always)]
+    fn layout_name<Layout: LayoutRules>() -> std::borrow::Cow<'static, str> {
+        // Arrays are declared only as members, so elements are declared with their qualifiers.
+        let elem = Lang::element_name(&T::member_name::<Layout>(), Layout::ALIGN16);
+        std::borrow::Cow::Owned(Lang::array_name(&elem, N))
+    }
+
+    #[inline(always)]
+    fn layout_definition<Layout: LayoutRules>() -> Option<String> {
+        None
+    }
+}
+
+/// Name of the array type in C-like shader languages.
+///
+/// Dimension is inserted before dimensions of the element type,
+/// so that `float[2]` becomes `float[3][2]`.
+#[cfg(all(
+    feature = "codegen",
+    any(feature = "glsl", feature = "hlsl", feature = "msl")
+))]
+fn c_array_name(elem: &str, len: usize) -> String {
+    match elem.find('[') {
+        None => format!("{elem}[{len}]"),
+        Some(idx) => format!("{}[{len}]{}", &elem[..idx], &elem[idx..]),
+    }
+}
+
+/// Declaration of the field in C-like shader languages.
+///
+/// Array dimensions are moved after the field name.
+#[cfg(all(
+    feature = "codegen",
+    any(feature = "glsl", feature = "hlsl", feature = "msl")
+))]
+fn c_field_declaration(name: &str, ty: &str) -> String {
+    match ty.find('[') {
+        None => format!("{ty} {name}"),
+        Some(idx) => format!("{} {name}{}", &ty[..idx], &ty[idx..]),
+    }
+}
+
+/// Definition of the unpacking function in C-like shader languages.
+#[cfg(all(
+    feature = "codegen",
+    any(feature = "glsl", feature = "hlsl", feature = "msl")
+))]
+fn c_unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String {
+    let mut definition = format!("{result} {name}({ty} value) {{\n    {result} result;\n");
+    for (field, expr) in fields {
+        definition.push_str(&format!("    result.{field} = {expr};\n"));
+    }
+    definition.push_str("    return result;\n}\n");
+    definition
+}
+
+/// Definition of the function returning single expression in C-like shader languages.
+#[cfg(all(
+    feature = "codegen",
+    any(feature = "glsl", feature = "hlsl", feature = "msl")
+))]
+fn c_function_definition(name: &str, params: &[(&str, &str)], result: &str, expr: &str) -> String {
+    let params = params
+        .iter()
+        .map(|(param, ty)| format!("{ty} {param}"))
+        .collect::<Vec<_>>();
+    format!(
+        "{result} {name}({}) {{\n    return {expr};\n}}\n",
+        params.join(", ")
+    )
+}
+
+/// Definition of the structure in C-like shader languages.
+#[cfg(all(
+    feature = "codegen",
+    any(feature = "glsl", feature = "hlsl", feature = "msl")
+))]
+fn c_struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String {
+    let mut definition = format!("struct {name} {{\n");
+    for (field, ty) in fields {
+        definition.push_str("    ");
+        definition.push_str(&c_field_declaration(field, ty));
+        definition.push_str(";\n");
+    }
+    definition.push_str("};\n");
+    definition
 }
@@ -1,3 +1,5 @@
+#![allow(non_camel_case_types)]
+
 use core::{
     any::TypeId,
     mem::{size_of, MaybeUninit},
@@ -9,13 +11,19 @@ pub enum Msl {}
 
 shader_scalar!(in Msl => {
     bool | x1 = Align1, x2 = Align2, x4 = Align4;
+    i8 as char | x1 = Align1, x2 = Align2, x4 = Align4;
+    u8 as uchar | x1 = Align1, x2 = Align2, x4 = Align4;
+    i16 as short | x1 = Align2, x2 = Align4, x4 = Align8;
+    u16 as ushort | x1 = Align2, x2 = Align4, x4 = Align8;
     i32 as int | x1 = Align4, x2 = Align8, x4 = Align16;
     u32 as uint | x1 = Align4, x2 = Align8, x4 = Align16;
+    i64 as long | x1 = Align8, x2 = Align16, x4 = Align32;
+    u64 as ulong | x1 = Align8, x2 = Align16, x4 = Align32;
+    f16 as half | x1 = Align2, x2 = Align4, x4 = Align8;
     f32 as float | x1 = Align4, x2 = Align8, x4 = Align16;
-    f64 as double | x1 = Align8, x2 = Align16, x4 = Align32;
 });
 
-macro_rules! vec_mat_repr {
+macro_rules! vec_repr {
     ($($t:ty)+) => {$(
         impl ShaderRepr<Msl> for vec<$t, 2> {
             type Align = align!(in Msl, $t, x2);
@@ -27,13 +35,15 @@ macro_rules! vec_mat_repr {
             }
         }
 
+        /// Three-component vectors have the size of four-component ones.
+        /// Use `packed_vec3` for tightly packed vectors.
         impl ShaderRepr<Msl> for vec<$t, 3> {
             type Align = align!(in Msl, $t, x4);
-            type Repr = Self;
+            type Repr = Aligned<Self::Align, Self>;
 
             #[inline(always)]
-            fn repr(&self) -> Self {
-                *self
+            fn repr(&self) -> Self::Repr {
+                Aligned::new(*self)
             }
         }
 
@@ -49,8 +59,14 @@ macro_rules! vec_mat_repr {
                 *self
             }
         }
+    )*};
+}
 
+vec_repr! { bool i8 u8 i16 u16 i32 u32 i64 u64 f16 f32 }
 
+/// MSL has only floating-point matrices.
+macro_rules! mat_repr {
+    ($($t:ty)+) => {$(
         impl<const N: usize> ShaderRepr<Msl> for mat<$t, N, 2> {
             type Align = align!(in Msl, $t, x2);
             type Repr = [Aligned<Self::Align, vec<$t, 2>>; N];
@@ -83,7 +99,57 @@ macro_rules! vec_mat_repr {
     )*};
 }
 
-vec_mat_repr! { bool i32 u32 f32 f64 }
+mat_repr! { f16 f32 }
+
+/// Tightly packed three-component vector.
+///
+/// Maps to `packed_float3`, `packed_half3`, `packed_int3` and `packed_uint3` in MSL.
+/// Unlike `vec3` it has the size of three scalars and alignment of one scalar.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct packed_vec3<T>(pub [T; 3]);
+
+impl<T> From<vec3<T>> for packed_vec3<T> {
+    #[inline(always)]
+    fn from(v: vec3<T>) -> Self {
+        packed_vec3(v.0)
+    }
+}
+
+impl<T> From<packed_vec3<T>> for vec3<T> {
+    #[inline(always)]
+    fn from(v: packed_vec3<T>) -> Self {
+        vec(v.0)
+    }
+}
+
+/// Handy type aliases for packed three-component vector.
+pub type packed_vec3i = packed_vec3<i32>;
+
+/// Handy type aliases for packed three-component vector.
+pub type packed_vec3u = packed_vec3<u32>;
+
+/// Handy type aliases for packed three-component vector.
+pub type packed_vec3h = packed_vec3<f16>;
+
+/// Handy type aliases for packed three-component vector.
+pub type packed_vec3f = packed_vec3<f32>;
+
+macro_rules! packed_vec3_repr {
+    ($($t:ty)+) => {$(
+        impl ShaderRepr<Msl> for packed_vec3<$t> {
+            type Align = align!(in Msl, $t);
+            type Repr = Self;
+
+            #[inline(always)]
+            fn repr(&self) -> Self {
+                *self
+            }
+        }
+    )*};
+}
+
+packed_vec3_repr! { i32 u32 f16 f32 }
 
 impl<T, const N: usize> ShaderRepr<Msl> for [T; N]
 where
@@ -113,49 +179,95 @@ where
 }
 
 #[cfg(feature = "codegen")]
-codegen_builtin!(in Hlsl => {
+impl ShaderLang for Msl {
+    #[inline(always)]
+    fn array_name(elem: &str, len: usize) -> String {
+        c_array_name(elem, len)
+    }
+
+    #[inline(always)]
+    fn struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String {
+        c_struct_definition(name, fields)
+    }
+
+    #[inline(always)]
+    fn constant_definition(name: &str, ty: &str, value: &str) -> String {
+        format!("constant {ty} {name} = {value};\n")
+    }
+
+    #[inline(always)]
+    fn bitcast(ty: &str, word: &str) -> String {
+        format!("as_type<{ty}>({word})")
+    }
+
+    #[inline(always)]
+    fn unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String {
+        c_unpack_function(name, ty, result, fields)
+    }
+
+    #[inline(always)]
+    fn function_definition(
+        name: &str,
+        params: &[(&str, &str)],
+        result: &str,
+        expr: &str,
+    ) -> String {
+        c_function_definition(name, params, result, expr)
+    }
+}
+
+#[cfg(feature = "codegen")]
+codegen_builtin!(in Msl => {
     vec2b as bool2,
     vec3b as bool3,
     vec4b as bool4,
+    vec2i8 as char2,
+    vec3i8 as char3,
+    vec4i8 as char4,
+    vec2u8 as uchar2,
+    vec3u8 as uchar3,
+    vec4u8 as uchar4,
+    vec2i16 as short2,
+    vec3i16 as short3,
+    vec4i16 as short4,
+    vec2u16 as ushort2,
+    vec3u16 as ushort3,
+    vec4u16 as ushort4,
     vec2<i32> as int2,
     vec3<i32> as int3,
     vec4<i32> as int4,
     vec2<u32> as uint2,
     vec3<u32> as uint3,
     vec4<u32> as uint4,
+    vec2i64 as long2,
+    vec3i64 as long3,
+    vec4i64 as long4,
+    vec2u64 as ulong2,
+    vec3u64 as ulong3,
+    vec4u64 as ulong4,
+    vec2<f16> as half2,
+    vec3<f16> as half3,
+    vec4<f16> as half4,
     vec2<f32> as float2,
     vec3<f32> as float3,
     vec4<f32> as float4,
-    vec2<f64> as double2,
-    vec3<f64> as double3,
-    vec4<f64> as double4,
-    mat2x2<bool> as bool2x2,
-    mat2x3<bool> as bool2x3,
-    mat2x4<bool> as bool2x4,
-    mat3x2<bool> as bool3x2,
-    mat3x3<bool> as bool3x3,
-    mat3x4<bool> as bool3x4,
-    mat4x2<bool> as bool4x2,
-    mat4x3<bool> as bool4x3,
-    mat4x4<bool> as bool4x4,
-    mat2x2<i32> as int2x2,
-    mat2x3<i32> as int2x3,
-    mat2x4<i32> as int2x4,
-    mat3x2<i32> as int3x2,
-    mat3x3<i32> as int3x3,
-    mat3x4<i32> as int3x4,
-    mat4x2<i32> as int4x2,
-    mat4x3<i32> as int4x3,
-    mat4x4<i32> as int4x4,
-    mat2x2<u32> as uint2x2,
-    mat2x3<u32> as uint2x3,
-    mat2x4<u32> as uint2x4,
-    mat3x2<u32> as uint3x2,
-    mat3x3<u32> as uint3x3,
-    mat3x4<u32> as uint3x4,
-    mat4x2<u32> as uint4x2,
-    mat4x3<u32> as uint4x3,
-    mat4x4<u32> as uint4x4,
+    packed_vec3<i32> as packed_int3,
+    packed_vec3<u32> as packed_uint3,
+    packed_vec3<f16> as packed_half3,
+    packed_vec3<f32> as packed_float3,
+});
+
+#[cfg(feature = "codegen")]
+codegen_builtin!(in Msl => matrices {
+    mat2x2<f16> as half2x2,
+    mat2x3<f16> as half2x3,
+    mat2x4<f16> as half2x4,
+    mat3x2<f16> as half3x2,
+    mat3x3<f16> as half3x3,
+    mat3x4<f16> as half3x4,
+    mat4x2<f16> as half4x2,
+    mat4x3<f16> as half4x3,
+    mat4x4<f16> as half4x4,
     mat2x2<f32> as float2x2,
     mat2x3<f32> as float2x3,
     mat2x4<f32> as float2x4,
@@ -165,13 +277,17 @@ codegen_builtin!(in Hlsl => {
     mat4x2<f32> as float4x2,
     mat4x3<f32> as float4x3,
     mat4x4<f32> as float4x4,
-    mat2x2<f64> as double2x2,
-    mat2x3<f64> as double2x3,
-    mat2x4<f64> as double2x4,
-    mat3x2<f64> as double3x2,
-    mat3x3<f64> as double3x3,
-    mat3x4<f64> as double3x4,
-    mat4x2<f64> as double4x2,
-    mat4x3<f64> as double4x3,
-    mat4x4<f64> as double4x4,
+});
+
+#[cfg(feature = "codegen")]
+codegen_packed!(in Msl => {
+    Unorm8x4 as vec4f => "unpack_unorm4x8_to_float(value)",
+    Snorm8x4 as vec4f => "unpack_snorm4x8_to_float(value)",
+    Unorm16x2 as vec2f => "unpack_unorm2x16_to_float(value)",
+    Snorm16x2 as vec2f => "unpack_snorm2x16_to_float(value)",
+    Unorm10_10_10_2 as vec4f => "unpack_unorm10a2_to_float(value)",
+    OctNormal as vec3f => "OctNormal_unfold(unpack_snorm2x16_to_float(value))"
+        where OctNormal_unfold(p: vec2f) => "normalize(float3(p - sign(p) * max(abs(p.x) + abs(p.y) - 1.0, 0.0), 1.0 - abs(p.x) - abs(p.y)))",
+    Rgb9e5 as vec3f => "float3((uint3(value) >> uint3(0, 9, 18)) & 511u) * exp2(float(value >> 27) - 24.0)",
+    Rg11b10f as vec3f => "float3(float2(as_type<half2>(((value << 4) & 0x7ff0u) | ((value << 9) & 0x7ff00000u))), float(as_type<half2>((value >> 17) & 0x7fe0u).x))",
 });
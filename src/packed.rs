@@ -0,0 +1,373 @@
+#![allow(non_camel_case_types)]
+
+use crate::{types::round_shift, *};
+
+/// Four unsigned normalized 8-bit components packed into `u32`.
+///
+/// Component `x` occupies the lowest byte.
+/// Matches `unpackUnorm4x8` in GLSL and `unpack4x8unorm` in WGSL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Unorm8x4(pub u32);
+
+/// Four signed normalized 8-bit components packed into `u32`.
+///
+/// Component `x` occupies the lowest byte.
+/// Matches `unpackSnorm4x8` in GLSL and `unpack4x8snorm` in WGSL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Snorm8x4(pub u32);
+
+/// Two unsigned normalized 16-bit components packed into `u32`.
+///
+/// Component `x` occupies the lower half.
+/// Matches `unpackUnorm2x16` in GLSL and `unpack2x16unorm` in WGSL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Unorm16x2(pub u32);
+
+/// Two signed normalized 16-bit components packed into `u32`.
+///
+/// Component `x` occupies the lower half.
+/// Matches `unpackSnorm2x16` in GLSL and `unpack2x16snorm` in WGSL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Snorm16x2(pub u32);
+
+/// Three unsigned normalized 10-bit components and 2-bit alpha packed into `u32`.
+///
+/// Component `x` occupies the lowest 10 bits and alpha the highest 2 bits.
+/// Matches `unpack_unorm10a2_to_float` in MSL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Unorm10_10_10_2(pub u32);
+
+/// Unit vector in octahedral encoding.
+///
+/// Vector is projected onto octahedron and unfolded onto a square,
+/// coordinates on the square are stored as `Snorm16x2`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct OctNormal(pub u32);
+
+/// Three unsigned 9-bit mantissas with shared 5-bit exponent packed into `u32`.
+///
+/// Component `x` occupies the lowest 9 bits and exponent the highest 5 bits.
+/// Same as `RGB9_E5` texture format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Rgb9e5(pub u32);
+
+/// Two unsigned 11-bit floats and one unsigned 10-bit float packed into `u32`.
+///
+/// Component `x` occupies the lowest 11 bits and `z` the highest 10 bits.
+/// Floats have 5-bit exponent as `f16` and no sign bit.
+/// Same as `R11F_G11F_B10F` texture format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Rg11b10f(pub u32);
+
+/// Converts `value` into unsigned normalized integer with `max` as 1.0.
+///
+/// Value is clamped to `[0, 1]` and rounded to nearest. NaN becomes zero.
+#[inline(always)]
+fn unorm(value: f32, max: u32) -> u32 {
+    (value.clamp(0.0, 1.0) * max as f32 + 0.5) as u32
+}
+
+/// Converts `value` into signed normalized integer with `max` as 1.0.
+///
+/// Value is clamped to `[-1, 1]` and rounded to nearest, ties away from zero.
+/// NaN becomes zero.
+#[inline(always)]
+fn snorm(value: f32, max: i32) -> i32 {
+    let value = value.clamp(-1.0, 1.0) * max as f32;
+    (value + 0.5f32.copysign(value)) as i32
+}
+
+/// Converts unsigned normalized integer with `max` as 1.0 into `f32`.
+#[inline(always)]
+fn from_unorm(value: u32, max: u32) -> f32 {
+    value as f32 / max as f32
+}
+
+/// Converts signed normalized integer with `max` as 1.0 into `f32`.
+///
+/// Both `-max` and `-max - 1` become -1.0.
+#[inline(always)]
+fn from_snorm(value: i32, max: i32) -> f32 {
+    (value as f32 / max as f32).max(-1.0)
+}
+
+/// Converts `value` into unsigned float with 5-bit exponent and `man_bits`-bit mantissa.
+///
+/// Value is rounded to nearest, ties to even.
+/// Negative values become zero and values too large become the largest finite value.
+/// Infinity and NaN are preserved.
+fn to_ufloat(value: f32, man_bits: u32) -> u32 {
+    let bits = value.to_bits();
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x7f_ffff;
+    let inf = 0x1f << man_bits;
+
+    if exp == 0xff && man != 0 {
+        return inf | (1 << (man_bits - 1));
+    }
+
+    if bits >> 31 != 0 {
+        return 0;
+    }
+
+    if exp == 0xff {
+        return inf;
+    }
+
+    // Exponent rebiased as in `f16`.
+    let exp = exp - 127 + 15;
+
+    if exp >= 0x1f {
+        return inf - 1;
+    }
+
+    let shift = 23 - man_bits;
+    if exp <= 0 {
+        // Subnormal, value is `man * 2^(-14 - man_bits)`.
+        if exp < -(man_bits as i32) {
+            return 0;
+        }
+        return round_shift(man | 0x80_0000, (shift as i32 + 1 - exp) as u32);
+    }
+
+    // Rounding may carry into the exponent.
+    round_shift(((exp as u32) << 23) | man, shift).min(inf - 1)
+}
+
+/// Converts unsigned float with 5-bit exponent and `man_bits`-bit mantissa into `f32`.
+fn from_ufloat(bits: u32, man_bits: u32) -> f32 {
+    let exp = bits >> man_bits;
+    let man = bits & ((1 << man_bits) - 1);
+    match exp {
+        // Subnormal, value is `man * 2^(-14 - man_bits)`.
+        0 => man as f32 * f32::from_bits((127 - 14 - man_bits) << 23),
+        0x1f => f32::from_bits(0x7f80_0000 | (man << (23 - man_bits))),
+        _ => f32::from_bits(((exp + 112) << 23) | (man << (23 - man_bits))),
+    }
+}
+
+impl Unorm8x4 {
+    /// Packs components of `value`.
+    pub fn pack(value: vec4<f32>) -> Self {
+        let [x, y, z, w] = value.0.map(|c| unorm(c, 0xff));
+        Unorm8x4(x | (y << 8) | (z << 16) | (w << 24))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec4<f32> {
+        vec([0, 8, 16, 24].map(|shift| from_unorm((self.0 >> shift) & 0xff, 0xff)))
+    }
+}
+
+impl Snorm8x4 {
+    /// Packs components of `value`.
+    pub fn pack(value: vec4<f32>) -> Self {
+        let [x, y, z, w] = value.0.map(|c| snorm(c, 0x7f) as u32 & 0xff);
+        Snorm8x4(x | (y << 8) | (z << 16) | (w << 24))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec4<f32> {
+        vec([0, 8, 16, 24].map(|shift| from_snorm((self.0 >> shift) as u8 as i8 as i32, 0x7f)))
+    }
+}
+
+impl Unorm16x2 {
+    /// Packs components of `value`.
+    pub fn pack(value: vec2<f32>) -> Self {
+        let [x, y] = value.0.map(|c| unorm(c, 0xffff));
+        Unorm16x2(x | (y << 16))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec2<f32> {
+        vec([0, 16].map(|shift| from_unorm((self.0 >> shift) & 0xffff, 0xffff)))
+    }
+}
+
+impl Snorm16x2 {
+    /// Packs components of `value`.
+    pub fn pack(value: vec2<f32>) -> Self {
+        let [x, y] = value.0.map(|c| snorm(c, 0x7fff) as u32 & 0xffff);
+        Snorm16x2(x | (y << 16))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec2<f32> {
+        vec([0, 16].map(|shift| from_snorm((self.0 >> shift) as u16 as i16 as i32, 0x7fff)))
+    }
+}
+
+impl Unorm10_10_10_2 {
+    /// Packs components of `value`.
+    pub fn pack(value: vec4<f32>) -> Self {
+        let [x, y, z] = [value.0[0], value.0[1], value.0[2]].map(|c| unorm(c, 0x3ff));
+        let w = unorm(value.0[3], 0x3);
+        Unorm10_10_10_2(x | (y << 10) | (z << 20) | (w << 30))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec4<f32> {
+        vec([
+            from_unorm(self.0 & 0x3ff, 0x3ff),
+            from_unorm((self.0 >> 10) & 0x3ff, 0x3ff),
+            from_unorm((self.0 >> 20) & 0x3ff, 0x3ff),
+            from_unorm(self.0 >> 30, 0x3),
+        ])
+    }
+}
+
+impl OctNormal {
+    /// Packs unit vector `value`.
+    ///
+    /// Vector does not have to be normalized, but must not be zero.
+    pub fn pack(value: vec3<f32>) -> Self {
+        let [x, y, z] = value.0;
+        let norm = x.abs() + y.abs() + z.abs();
+        let (x, y) = (x / norm, y / norm);
+
+        // Lower hemisphere is folded over the diagonals.
+        let (x, y) = match z < 0.0 {
+            true => ((1.0 - y.abs()) * x.signum(), (1.0 - x.abs()) * y.signum()),
+            false => (x, y),
+        };
+        OctNormal(Snorm16x2::pack(vec([x, y])).0)
+    }
+}
+
+impl Rgb9e5 {
+    /// Largest value that can be packed.
+    pub const MAX: f32 = 65408.0;
+
+    /// Packs components of `value`.
+    ///
+    /// Components are clamped to `[0, MAX]`, NaN becomes zero.
+    pub fn pack(value: vec3<f32>) -> Self {
+        let [r, g, b] = value.0.map(|c| match c > 0.0 {
+            true => c.min(Self::MAX),
+            false => 0.0,
+        });
+        let max = r.max(g).max(b);
+
+        // Exponent is chosen so that the largest component fits into 9 bits
+        // with `2^(exp - 24)` scale.
+        let floor_log2 = ((max.to_bits() >> 23) as i32 - 127).max(-16);
+        let scale = |exp: i32| f32::from_bits(((exp - 24 + 127) as u32) << 23);
+        let mut exp = floor_log2 + 16;
+        if (max / scale(exp) + 0.5) as u32 == 0x200 {
+            exp += 1;
+        }
+
+        let [r, g, b] = [r, g, b].map(|c| (c / scale(exp) + 0.5) as u32);
+        Rgb9e5(r | (g << 9) | (b << 18) | ((exp as u32) << 27))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec3<f32> {
+        let scale = f32::from_bits(((self.0 >> 27) + 127 - 24) << 23);
+        vec([0, 9, 18].map(|shift| ((self.0 >> shift) & 0x1ff) as f32 * scale))
+    }
+}
+
+impl Rg11b10f {
+    /// Packs components of `value`.
+    pub fn pack(value: vec3<f32>) -> Self {
+        let [r, g, b] = value.0;
+        Rg11b10f(to_ufloat(r, 6) | (to_ufloat(g, 6) << 11) | (to_ufloat(b, 5) << 22))
+    }
+
+    /// Unpacks components as the shader does.
+    pub fn unpack(self) -> vec3<f32> {
+        vec([
+            from_ufloat(self.0 & 0x7ff, 6),
+            from_ufloat((self.0 >> 11) & 0x7ff, 6),
+            from_ufloat(self.0 >> 22, 5),
+        ])
+    }
+}
+
+macro_rules! packed_from {
+    ($($t:ident($v:ty)),+ $(,)?) => {$(
+        impl From<$v> for $t {
+            #[inline(always)]
+            fn from(value: $v) -> Self {
+                $t::pack(value)
+            }
+        }
+
+        impl From<$t> for $v {
+            #[inline(always)]
+            fn from(value: $t) -> Self {
+                value.unpack()
+            }
+        }
+    )+};
+}
+
+packed_from! {
+    Unorm8x4(vec4<f32>),
+    Snorm8x4(vec4<f32>),
+    Unorm16x2(vec2<f32>),
+    Snorm16x2(vec2<f32>),
+    Unorm10_10_10_2(vec4<f32>),
+    Rgb9e5(vec3<f32>),
+    Rg11b10f(vec3<f32>),
+}
+
+/// Decoding requires normalization, so only encoding is provided.
+impl From<vec3<f32>> for OctNormal {
+    #[inline(always)]
+    fn from(value: vec3<f32>) -> Self {
+        OctNormal::pack(value)
+    }
+}
+
+macro_rules! packed_repr {
+    ($($t:ident),+ $(,)?) => {$(
+        /// Packed values are laid out as `u32` in all languages and layouts.
+        impl<Lang, Layout> ShaderRepr<Lang, Layout> for $t
+        where
+            u32: ShaderRepr<Lang, Layout>,
+        {
+            type Align = <u32 as ShaderRepr<Lang, Layout>>::Align;
+            type Repr = <u32 as ShaderRepr<Lang, Layout>>::Repr;
+
+            #[inline(always)]
+            fn repr(&self) -> Self::Repr {
+                self.0.repr()
+            }
+        }
+
+        #[cfg(feature = "codegen")]
+        impl<Lang> ShaderLoad<Lang> for $t
+        where
+            Lang: ShaderLang,
+            $t: CodeGen<Lang>,
+            u32: ShaderLoad<Lang>,
+        {
+            #[inline(always)]
+            fn load(word: &dyn Fn(usize) -> String, offset: usize) -> String {
+                <u32 as ShaderLoad<Lang>>::load(word, offset)
+            }
+        }
+    )+};
+}
+
+packed_repr! {
+    Unorm8x4,
+    Snorm8x4,
+    Unorm16x2,
+    Snorm16x2,
+    Unorm10_10_10_2,
+    OctNormal,
+    Rgb9e5,
+    Rg11b10f,
+}
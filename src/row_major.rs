@@ -0,0 +1,85 @@
+use core::marker::PhantomData;
+
+use crate::*;
+
+/// Layout adapter that stores matrices in row-major order.
+///
+/// `mat<T, N, M>` is stored as `N` columns of `M` components.
+/// With this adapter it is transposed and stored as `M` rows of `N` components,
+/// each row laid out as a vector by the rules of the layout `L`.
+/// Everything else is laid out as in `L`.
+pub struct RowMajor<L>(PhantomData<fn() -> L>);
+
+impl<L> LayoutRules for RowMajor<L>
+where
+    L: LayoutRules,
+{
+    const ROW_MAJOR: bool = true;
+    const ALIGN16: bool = L::ALIGN16;
+    const SUFFIX: &'static str = "_RowMajor";
+}
+
+impl<Lang, L, T, const N: usize> ShaderRepr<Lang, RowMajor<L>> for vec<T, N>
+where
+    vec<T, N>: ShaderRepr<Lang, L>,
+{
+    type Align = <vec<T, N> as ShaderRepr<Lang, L>>::Align;
+    type Repr = <vec<T, N> as ShaderRepr<Lang, L>>::Repr;
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        <vec<T, N> as ShaderRepr<Lang, L>>::repr(self)
+    }
+}
+
+impl<Lang, L, T, const N: usize, const M: usize> ShaderRepr<Lang, RowMajor<L>> for mat<T, N, M>
+where
+    T: Copy,
+    mat<T, N, M>: 'static,
+    mat<T, M, N>: ShaderRepr<Lang, L>,
+{
+    type Align = <mat<T, M, N> as ShaderRepr<Lang, L>>::Align;
+    type Repr = <mat<T, M, N> as ShaderRepr<Lang, L>>::Repr;
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        self.transpose().repr()
+    }
+}
+
+/// Element of the array laid out with `RowMajor<L>`.
+///
+/// Arrays are laid out by the rules of `L`
+/// with elements laid out with `RowMajor<L>`.
+#[doc(hidden)]
+#[repr(transparent)]
+pub struct RowMajorElem<T>(pub(crate) T);
+
+impl<Lang, L, T> ShaderRepr<Lang, L> for RowMajorElem<T>
+where
+    T: ShaderRepr<Lang, RowMajor<L>>,
+{
+    type Align = T::Align;
+    type Repr = T::Repr;
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        self.0.repr()
+    }
+}
+
+impl<Lang, L, T, const N: usize> ShaderRepr<Lang, RowMajor<L>> for [T; N]
+where
+    T: ShaderRepr<Lang, RowMajor<L>>,
+    [RowMajorElem<T>; N]: ShaderRepr<Lang, L>,
+{
+    type Align = <[RowMajorElem<T>; N] as ShaderRepr<Lang, L>>::Align;
+    type Repr = <[RowMajorElem<T>; N] as ShaderRepr<Lang, L>>::Repr;
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        // SAFETY: `RowMajorElem` is transparent wrapper.
+        let elems = unsafe { &*(self as *const [T; N]).cast::<[RowMajorElem<T>; N]>() };
+        elems.repr()
+    }
+}
@@ -1,4 +1,4 @@
-#![allow(non_camel_case_types)]
+#![allow(non_camel_case_types, clippy::too_many_arguments)]
 
 /// 16-bit floating point type compatible with shader languages.
 #[derive(Clone, Copy, Debug, Default)]
@@ -6,6 +6,98 @@
 #[allow(non_camel_case_types)]
 pub struct f16(pub u16);
 
+impl f16 {
+    /// Converts `f32` into nearest `f16`, rounding ties to even.
+    ///
+    /// Values too large for `f16` become infinities,
+    /// values too small become subnormals or zeros.
+    /// NaN stays NaN.
+    pub const fn from_f32(value: f32) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exp = ((bits >> 23) & 0xff) as i32;
+        let man = bits & 0x7f_ffff;
+
+        if exp == 0xff {
+            // Infinity or NaN. Quiet bit keeps NaN from turning into infinity.
+            return match man {
+                0 => f16(sign | 0x7c00),
+                _ => f16(sign | 0x7e00 | (man >> 13) as u16),
+            };
+        }
+
+        // Exponent rebiased for `f16`.
+        let exp = exp - 127 + 15;
+
+        if exp >= 0x1f {
+            return f16(sign | 0x7c00);
+        }
+
+        if exp <= 0 {
+            // Subnormal `f16`, value is `man * 2^-24`.
+            if exp < -10 {
+                return f16(sign);
+            }
+
+            let man = man | 0x80_0000;
+            let shift = (14 - exp) as u32;
+            return f16(sign | round_shift(man, shift) as u16);
+        }
+
+        // Rounding may carry into the exponent, up to infinity.
+        let bits = ((exp as u32) << 23) | man;
+        f16(sign | round_shift(bits, 13) as u16)
+    }
+
+    /// Converts `f16` into `f32`.
+    ///
+    /// Conversion is exact.
+    pub const fn to_f32(self) -> f32 {
+        let bits = self.0 as u32;
+        let sign = (bits & 0x8000) << 16;
+        let exp = (bits >> 10) & 0x1f;
+        let man = bits & 0x3ff;
+
+        let bits = match (exp, man) {
+            (0, 0) => sign,
+            (0, _) => {
+                // Subnormal `f16` is normal `f32`.
+                let top = 31 - man.leading_zeros();
+                sign | ((top + 103) << 23) | ((man << (23 - top)) & 0x7f_ffff)
+            }
+            (0x1f, _) => sign | 0x7f80_0000 | (man << 13),
+            _ => sign | ((exp + 112) << 23) | (man << 13),
+        };
+        f32::from_bits(bits)
+    }
+}
+
+/// Shifts `value` right by `shift` bits rounding to nearest, ties to even.
+pub(crate) const fn round_shift(value: u32, shift: u32) -> u32 {
+    let half = 1 << (shift - 1);
+    let rem = value & ((1 << shift) - 1);
+    let result = value >> shift;
+    if rem > half || (rem == half && result & 1 == 1) {
+        result + 1
+    } else {
+        result
+    }
+}
+
+impl From<f32> for f16 {
+    #[inline(always)]
+    fn from(value: f32) -> Self {
+        f16::from_f32(value)
+    }
+}
+
+impl From<f16> for f32 {
+    #[inline(always)]
+    fn from(value: f16) -> Self {
+        value.to_f32()
+    }
+}
+
 /// Vector type compatible with shader languages.
 #[derive(Clone, Copy, Debug)]
 #[repr(transparent)]
@@ -286,6 +378,60 @@ pub type mat3f = mat3x3f;
 /// Short-hand for `mat4x4`.
 pub type mat4f = mat4x4f;
 
+/// Handy type aliases for two-component vector.
+pub type vec2i8 = vec<i8, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3i8 = vec<i8, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4i8 = vec<i8, 4>;
+
+/// Handy type aliases for two-component vector.
+pub type vec2u8 = vec<u8, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3u8 = vec<u8, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4u8 = vec<u8, 4>;
+
+/// Handy type aliases for two-component vector.
+pub type vec2i16 = vec<i16, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3i16 = vec<i16, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4i16 = vec<i16, 4>;
+
+/// Handy type aliases for two-component vector.
+pub type vec2u16 = vec<u16, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3u16 = vec<u16, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4u16 = vec<u16, 4>;
+
+/// Handy type aliases for two-component vector.
+pub type vec2i64 = vec<i64, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3i64 = vec<i64, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4i64 = vec<i64, 4>;
+
+/// Handy type aliases for two-component vector.
+pub type vec2u64 = vec<u64, 2>;
+
+/// Handy type aliases for three-component vector.
+pub type vec3u64 = vec<u64, 3>;
+
+/// Handy type aliases for four-component vector.
+pub type vec4u64 = vec<u64, 4>;
+
 #[inline(always)]
 pub fn vec2<T>(x: T, y: T) -> vec2<T> {
     vec([x, y])
@@ -446,3 +592,16 @@ pub fn mat4<T>(
         m00, m01, m02, m03, m10, m11, m12, m13, m20, m21, m22, m23, m30, m31, m32, m33,
     )
 }
+
+impl<T, const N: usize, const M: usize> mat<T, N, M>
+where
+    T: Copy,
+{
+    /// Returns transposed matrix.
+    #[inline(always)]
+    pub fn transpose(&self) -> mat<T, M, N> {
+        mat(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[j][i])
+        }))
+    }
+}
@@ -7,17 +7,40 @@ use crate::*;
 
 pub enum Wgsl {}
 
+/// Layout of the `storage` address space.
+pub type WgslStorage = DefaultLayout;
+
+/// Layout of the `uniform` address space.
+///
+/// Arrays and structures must start at a multiple of 16 bytes,
+/// array elements must be a multiple of 16 bytes apart
+/// and members after a structure must start at least 16-byte rounded size after it.
+/// Arrays of scalars and `vec2` are padded like in `Std140`,
+/// other array elements must implement [`UniformElement`].
+///
+/// WGSL lays out types the same way in every address space
+/// and rejects types that violate these constraints instead of padding them,
+/// so code-generation declares the padding.
+/// Padded array elements are declared as `vec4` with the value in the first components
+/// and structures are declared with `_Uniform` suffix and the first member aligned to 16 bytes.
+pub enum WgslUniform {}
+
+impl LayoutRules for WgslUniform {
+    const ALIGN16: bool = true;
+    const SUFFIX: &'static str = "_Uniform";
+}
+
+// `f16` requires `enable f16;` directive.
 shader_scalar!(in Wgsl => {
-    bool | x1 = Align1, x2 = Align2, x4 = Align4;
-    i32 as int | x1 = Align4, x2 = Align8, x4 = Align16;
-    u32 as uint | x1 = Align4, x2 = Align8, x4 = Align16;
-    f32 as float | x1 = Align4, x2 = Align8, x4 = Align16;
-    f64 as double | x1 = Align8, x2 = Align16, x4 = Align32;
+    i32 | x1 = Align4, x2 = Align8, x4 = Align16;
+    u32 | x1 = Align4, x2 = Align8, x4 = Align16;
+    f16 | x1 = Align2, x2 = Align4, x4 = Align8;
+    f32 | x1 = Align4, x2 = Align8, x4 = Align16;
 });
 
 macro_rules! vec_mat_repr {
-    ($($t:ty)+) => {$(
-        impl ShaderRepr<Wgsl> for vec<$t, 2> {
+    (in $layout:ident => $($t:ty)+) => {$(
+        impl ShaderRepr<Wgsl, $layout> for vec<$t, 2> {
             type Align = align!(in Wgsl, $t, x2);
             type Repr = Self;
 
@@ -27,7 +50,7 @@ macro_rules! vec_mat_repr {
             }
         }
 
-        impl ShaderRepr<Wgsl> for vec<$t, 3> {
+        impl ShaderRepr<Wgsl, $layout> for vec<$t, 3> {
             type Align = align!(in Wgsl, $t, x4);
             type Repr = Self;
 
@@ -37,10 +60,7 @@ macro_rules! vec_mat_repr {
             }
         }
 
-        impl ShaderRepr<Wgsl> for vec<$t, 4>
-        where
-            $t: ShaderScalar<Wgsl>,
-        {
+        impl ShaderRepr<Wgsl, $layout> for vec<$t, 4> {
             type Align = align!(in Wgsl, $t, x4);
             type Repr = Self;
 
@@ -50,8 +70,7 @@ macro_rules! vec_mat_repr {
             }
         }
 
-
-        impl<const N: usize> ShaderRepr<Wgsl> for mat<$t, N, 2> {
+        impl<const N: usize> ShaderRepr<Wgsl, $layout> for mat<$t, N, 2> {
             type Align = align!(in Wgsl, $t, x2);
             type Repr = [Aligned<Self::Align, vec<$t, 2>>; N];
 
@@ -61,7 +80,7 @@ macro_rules! vec_mat_repr {
             }
         }
 
-        impl<const N: usize> ShaderRepr<Wgsl> for mat<$t, N, 3> {
+        impl<const N: usize> ShaderRepr<Wgsl, $layout> for mat<$t, N, 3> {
             type Align = align!(in Wgsl, $t, x4);
             type Repr = [Aligned<Self::Align, vec<$t, 3>>; N];
 
@@ -71,7 +90,7 @@ macro_rules! vec_mat_repr {
             }
         }
 
-        impl<const N: usize> ShaderRepr<Wgsl> for mat<$t, N, 4> {
+        impl<const N: usize> ShaderRepr<Wgsl, $layout> for mat<$t, N, 4> {
             type Align = align!(in Wgsl, $t, x4);
             type Repr = [Aligned<Self::Align, vec<$t, 4>>; N];
 
@@ -83,11 +102,90 @@ macro_rules! vec_mat_repr {
     )*};
 }
 
-vec_mat_repr! { bool i32 u32 f32 f64 }
+vec_mat_repr! { in WgslStorage => i32 u32 f16 f32 }
+vec_mat_repr! { in WgslUniform => i32 u32 f16 f32 }
+
+/// Marker trait for host-shareable types.
+///
+/// Only values of such types can be stored in `uniform` and `storage` buffers.
+/// `bool` is not host-shareable and WGSL has no 64-bit floats,
+/// so neither of them implements `ShaderRepr<Wgsl>`.
+///
+/// Implemented by derive macro when all fields are host-shareable.
+/// It implies `ShaderRepr<Wgsl>` impls in both layouts,
+/// so derived impls of generic types require only it from the generic fields.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not host-shareable in WGSL",
+    note = "use `u32` instead of `bool` and `f32` instead of `f64`"
+)]
+pub trait HostShareable: ShaderRepr<Wgsl, WgslStorage> + ShaderRepr<Wgsl, WgslUniform> {}
+
+macro_rules! host_shareable {
+    ($($t:ty)+) => {$(
+        impl HostShareable for $t {}
+        impl HostShareable for vec<$t, 2> {}
+        impl HostShareable for vec<$t, 3> {}
+        impl HostShareable for vec<$t, 4> {}
+        impl<const N: usize> HostShareable for mat<$t, N, 2> {}
+        impl<const N: usize> HostShareable for mat<$t, N, 3> {}
+        impl<const N: usize> HostShareable for mat<$t, N, 4> {}
+    )*};
+}
+
+host_shareable! { i32 u32 f16 f32 }
+
+impl HostShareable for Unorm8x4 {}
+impl HostShareable for Snorm8x4 {}
+impl HostShareable for Unorm16x2 {}
+impl HostShareable for Snorm16x2 {}
+impl HostShareable for Unorm10_10_10_2 {}
+impl HostShareable for OctNormal {}
+impl HostShareable for Rgb9e5 {}
+impl HostShareable for Rg11b10f {}
+
+impl<T, const N: usize> HostShareable for [T; N] where T: HostShareable + UniformElement {}
+
+/// Marker trait for types that can be elements of arrays in `uniform` address space.
+///
+/// Array elements must be a multiple of 16 bytes apart there.
+/// Scalars and `vec2` are padded to `vec4`,
+/// other types must be aligned to 16 bytes by WGSL.
+/// `f16` values and matrices with `vec2` columns are not,
+/// and no built-in type of the padded size has their alignment,
+/// so derived structures cannot have arrays of them.
+///
+/// Implemented by derive macro, as structures are aligned to 16 bytes in the layout.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be an element of arrays in WGSL `uniform` address space",
+    note = "use 32-bit scalars and vectors or matrices with `vec3` and `vec4` columns"
+)]
+pub trait UniformElement {}
+
+macro_rules! uniform_element {
+    ($($t:ty)+) => {$(
+        impl UniformElement for $t {}
+        impl<const N: usize> UniformElement for vec<$t, N> {}
+        impl<const N: usize> UniformElement for mat<$t, N, 3> {}
+        impl<const N: usize> UniformElement for mat<$t, N, 4> {}
+    )*};
+}
+
+uniform_element! { i32 u32 f32 }
+
+impl UniformElement for Unorm8x4 {}
+impl UniformElement for Snorm8x4 {}
+impl UniformElement for Unorm16x2 {}
+impl UniformElement for Snorm16x2 {}
+impl UniformElement for Unorm10_10_10_2 {}
+impl UniformElement for OctNormal {}
+impl UniformElement for Rgb9e5 {}
+impl UniformElement for Rg11b10f {}
+
+impl<T, const N: usize> UniformElement for [T; N] where T: UniformElement {}
 
-impl<T, const N: usize> ShaderRepr<Wgsl> for [T; N]
+impl<T, const N: usize> ShaderRepr<Wgsl, WgslStorage> for [T; N]
 where
-    T: ShaderRepr<Wgsl>,
+    T: ShaderRepr<Wgsl, WgslStorage>,
 {
     type Align = T::Align;
     type Repr = [Aligned<Self::Align, T::Repr>; N];
@@ -112,11 +210,119 @@ where
     }
 }
 
+/// Array elements are a multiple of 16 bytes apart in `uniform` address space.
+impl<T, const N: usize> ShaderRepr<Wgsl, WgslUniform> for [T; N]
+where
+    T: ShaderRepr<Wgsl, WgslUniform> + UniformElement,
+{
+    type Align = (Align16, <T as ShaderRepr<Wgsl, WgslUniform>>::Align);
+    type Repr = [Aligned<Self::Align, <T as ShaderRepr<Wgsl, WgslUniform>>::Repr>; N];
+
+    #[inline(always)]
+    fn repr(&self) -> Self::Repr {
+        if TypeId::of::<T>() == TypeId::of::<<T as ShaderRepr<Wgsl, WgslUniform>>::Repr>()
+            && size_of::<Self::Repr>() == size_of::<Self>()
+        {
+            // SAFETY: Element type matches layout.
+            unsafe { core::mem::transmute_copy(self) }
+        } else {
+            // SAFETY: Array of `MaybeUninit` is always initialized.
+            let mut array: [MaybeUninit<_>; N] = unsafe { MaybeUninit::uninit().assume_init() };
+            for (i, elem) in self.iter().enumerate() {
+                array[i] =
+                    MaybeUninit::new(Aligned::new(ShaderRepr::<Wgsl, WgslUniform>::repr(elem)));
+            }
+
+            // SAFETY: Every element of `array` was initialized.
+            array.map(|elem| unsafe { MaybeUninit::assume_init(elem) })
+        }
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl ShaderLang for Wgsl {
+    #[inline(always)]
+    fn array_name(elem: &str, len: usize) -> String {
+        format!("array<{elem}, {len}>")
+    }
+
+    #[inline(always)]
+    fn struct_definition(name: &str, fields: &[(&str, std::borrow::Cow<'static, str>)]) -> String {
+        Self::layout_struct_definition(name, fields, false)
+    }
+
+    fn layout_struct_definition(
+        name: &str,
+        fields: &[(&str, std::borrow::Cow<'static, str>)],
+        align16: bool,
+    ) -> String {
+        let mut definition = format!("struct {name} {{\n");
+        for (idx, (field, ty)) in fields.iter().enumerate() {
+            // Alignment of the first member aligns the structure and rounds its size up.
+            let align = match align16 && idx == 0 {
+                false => "",
+                true => "@align(16) ",
+            };
+            definition.push_str(&format!("    {align}{field}: {ty},\n"));
+        }
+        definition.push_str("}\n");
+        definition
+    }
+
+    #[inline(always)]
+    fn element_name(ty: &str, align16: bool) -> String {
+        // Elements other than scalars and `vec2` are aligned to 16 bytes, see `UniformElement`.
+        match (align16, ty) {
+            (true, "i32" | "u32" | "f32") => format!("vec4<{ty}>"),
+            (true, "vec2<i32>" | "vec2<u32>" | "vec2<f32>") => format!("vec4<{}", &ty[5..]),
+            _ => ty.to_owned(),
+        }
+    }
+
+    #[inline(always)]
+    fn constant_definition(name: &str, ty: &str, value: &str) -> String {
+        format!("const {name}: {ty} = {value};\n")
+    }
+
+    #[inline(always)]
+    fn int_literal(value: i32) -> String {
+        format!("{value}i")
+    }
+
+    #[inline(always)]
+    fn bitcast(ty: &str, word: &str) -> String {
+        format!("bitcast<{ty}>({word})")
+    }
+
+    fn unpack_function(name: &str, ty: &str, result: &str, fields: &[(&str, String)]) -> String {
+        let mut definition =
+            format!("fn {name}(value: {ty}) -> {result} {{\n    var result: {result};\n");
+        for (field, expr) in fields {
+            definition.push_str(&format!("    result.{field} = {expr};\n"));
+        }
+        definition.push_str("    return result;\n}\n");
+        definition
+    }
+
+    fn function_definition(
+        name: &str,
+        params: &[(&str, &str)],
+        result: &str,
+        expr: &str,
+    ) -> String {
+        let params = params
+            .iter()
+            .map(|(param, ty)| format!("{param}: {ty}"))
+            .collect::<Vec<_>>();
+        format!(
+            "fn {name}({}) -> {result} {{\n    return {expr};\n}}\n",
+            params.join(", ")
+        )
+    }
+}
+
 #[cfg(feature = "codegen")]
 codegen_builtin!(in Wgsl => {
-    vec2<bool>,
-    vec3<bool>,
-    vec4<bool>,
     vec2<i32>,
     vec3<i32>,
     vec4<i32>,
@@ -126,18 +332,13 @@ codegen_builtin!(in Wgsl => {
     vec2<f32>,
     vec3<f32>,
     vec4<f32>,
-    vec2<f64>,
-    vec3<f64>,
-    vec4<f64>,
-    mat2x2<bool>,
-    mat2x3<bool>,
-    mat2x4<bool>,
-    mat3x2<bool>,
-    mat3x3<bool>,
-    mat3x4<bool>,
-    mat4x2<bool>,
-    mat4x3<bool>,
-    mat4x4<bool>,
+    vec2<f16>,
+    vec3<f16>,
+    vec4<f16>,
+});
+
+#[cfg(feature = "codegen")]
+codegen_builtin!(in Wgsl => matrices {
     mat2x2<i32>,
     mat2x3<i32>,
     mat2x4<i32>,
@@ -165,13 +366,26 @@ codegen_builtin!(in Wgsl => {
     mat4x2<f32>,
     mat4x3<f32>,
     mat4x4<f32>,
-    mat2x2<f64>,
-    mat2x3<f64>,
-    mat2x4<f64>,
-    mat3x2<f64>,
-    mat3x3<f64>,
-    mat3x4<f64>,
-    mat4x2<f64>,
-    mat4x3<f64>,
-    mat4x4<f64>,
+    mat2x2<f16>,
+    mat2x3<f16>,
+    mat2x4<f16>,
+    mat3x2<f16>,
+    mat3x3<f16>,
+    mat3x4<f16>,
+    mat4x2<f16>,
+    mat4x3<f16>,
+    mat4x4<f16>,
+});
+
+#[cfg(feature = "codegen")]
+codegen_packed!(in Wgsl => {
+    Unorm8x4 as vec4f => "unpack4x8unorm(value)",
+    Snorm8x4 as vec4f => "unpack4x8snorm(value)",
+    Unorm16x2 as vec2f => "unpack2x16unorm(value)",
+    Snorm16x2 as vec2f => "unpack2x16snorm(value)",
+    Unorm10_10_10_2 as vec4f => "vec4<f32>((vec4<u32>(value) >> vec4<u32>(0u, 10u, 20u, 30u)) & vec4<u32>(1023u, 1023u, 1023u, 3u)) / vec4<f32>(1023.0, 1023.0, 1023.0, 3.0)",
+    OctNormal as vec3f => "OctNormal_unfold(unpack2x16snorm(value))"
+        where OctNormal_unfold(p: vec2f) => "normalize(vec3<f32>(p - sign(p) * max(abs(p.x) + abs(p.y) - 1.0, 0.0), 1.0 - abs(p.x) - abs(p.y)))",
+    Rgb9e5 as vec3f => "vec3<f32>((vec3<u32>(value) >> vec3<u32>(0u, 9u, 18u)) & vec3<u32>(511u)) * exp2(f32(value >> 27u) - 24.0)",
+    Rg11b10f as vec3f => "vec3<f32>(unpack2x16float(((value << 4u) & 0x7ff0u) | ((value << 9u) & 0x7ff00000u)), unpack2x16float((value >> 17u) & 0x7fe0u).x)",
 });
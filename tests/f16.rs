@@ -0,0 +1,62 @@
+//! Conversions between `f32` and `f16`.
+
+use shader_data::f16;
+
+#[test]
+fn round_trip() {
+    for bits in 0..=u16::MAX {
+        let value = f16(bits).to_f32();
+        if value.is_nan() {
+            assert!(f16::from_f32(value).to_f32().is_nan());
+        } else {
+            assert_eq!(f16::from_f32(value).0, bits, "{value}");
+        }
+    }
+}
+
+#[test]
+fn special_values() {
+    assert_eq!(f16::from_f32(0.0).0, 0x0000);
+    assert_eq!(f16::from_f32(-0.0).0, 0x8000);
+    assert_eq!(f16::from_f32(1.0).0, 0x3c00);
+    assert_eq!(f16::from_f32(-2.0).0, 0xc000);
+    assert_eq!(f16::from_f32(65504.0).0, 0x7bff);
+    assert_eq!(f16::from_f32(f32::INFINITY).0, 0x7c00);
+    assert_eq!(f16::from_f32(f32::NEG_INFINITY).0, 0xfc00);
+    assert!(f16::from_f32(f32::NAN).to_f32().is_nan());
+
+    // Overflow.
+    assert_eq!(f16::from_f32(65520.0).0, 0x7c00);
+    assert_eq!(f16::from_f32(1.0e10).0, 0x7c00);
+
+    // Smallest subnormal and underflow.
+    assert_eq!(f16(0x0001).to_f32(), 2.0f32.powi(-24));
+    assert_eq!(f16::from_f32(2.0f32.powi(-25)).0, 0x0000);
+    assert_eq!(f16::from_f32(2.0f32.powi(-25) * 1.5).0, 0x0001);
+    assert_eq!(f16::from_f32(-1.0e-10).0, 0x8000);
+}
+
+#[test]
+fn rounding() {
+    // Halfway between 1.0 and next value rounds to even.
+    assert_eq!(f16::from_f32(1.0 + 2.0f32.powi(-11)).0, 0x3c00);
+    assert_eq!(f16::from_f32(1.0 + 3.0 * 2.0f32.powi(-11)).0, 0x3c02);
+    assert_eq!(
+        f16::from_f32(1.0 + 2.0f32.powi(-11) + 2.0f32.powi(-20)).0,
+        0x3c01
+    );
+
+    // Largest subnormal rounds up into smallest normal.
+    assert_eq!(f16::from_f32(2.0f32.powi(-14) - 2.0f32.powi(-26)).0, 0x0400);
+
+    // Every `f32` between two `f16` values rounds to the nearest one.
+    for bits in 0..0x7bffu16 {
+        let low = f16(bits).to_f32();
+        let high = f16(bits + 1).to_f32();
+        let mid = (low + high) / 2.0;
+        let even = if bits % 2 == 0 { bits } else { bits + 1 };
+        assert_eq!(f16::from_f32(mid).0, even);
+        assert_eq!(f16::from_f32(f32::from_bits(mid.to_bits() - 1)).0, bits);
+        assert_eq!(f16::from_f32(f32::from_bits(mid.to_bits() + 1)).0, bits + 1);
+    }
+}
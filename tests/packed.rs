@@ -0,0 +1,203 @@
+//! Packing of normalized values.
+
+use shader_data::{
+    vec, vec2, vec3, vec4, CodeGen, Glsl, Hlsl, Msl, OctNormal, Rg11b10f, Rgb9e5, ShaderFields,
+    ShaderRepr, Snorm16x2, Snorm8x4, Unorm10_10_10_2, Unorm16x2, Unorm8x4, Wgsl,
+};
+
+#[test]
+fn unorm() {
+    assert_eq!(Unorm8x4::pack(vec4(0.0, 1.0, 0.5, 1.0)).0, 0xff80ff00);
+    assert_eq!(Unorm16x2::pack(vec2(1.0, 0.25)).0, 0x4000ffff);
+
+    // Out of range values are clamped, NaN becomes zero.
+    assert_eq!(Unorm8x4::pack(vec4(-1.0, 2.0, f32::NAN, 0.0)).0, 0x0000ff00);
+
+    // Rounding to nearest.
+    assert_eq!(
+        Unorm8x4::pack(vec4(0.5 / 255.0, 0.49 / 255.0, 0.0, 0.0)).0,
+        0x01
+    );
+
+    for bits in [0x00000000, 0xffffffff, 0x12345678, 0x80ff7f01] {
+        assert_eq!(Unorm8x4::pack(Unorm8x4(bits).unpack()).0, bits);
+        assert_eq!(Unorm16x2::pack(Unorm16x2(bits).unpack()).0, bits);
+        assert_eq!(
+            Unorm10_10_10_2::pack(Unorm10_10_10_2(bits).unpack()).0,
+            bits
+        );
+    }
+}
+
+#[test]
+fn snorm() {
+    assert_eq!(Snorm8x4::pack(vec4(0.0, 1.0, -1.0, -0.5)).0, 0xc0817f00);
+    assert_eq!(Snorm16x2::pack(vec2(1.0, -1.0)).0, 0x80017fff);
+
+    // Out of range values are clamped, NaN becomes zero.
+    assert_eq!(Snorm8x4::pack(vec4(-2.0, 2.0, f32::NAN, 0.0)).0, 0x00007f81);
+
+    // Both -127 and -128 unpack to -1.
+    assert_eq!(Snorm8x4(0x8081).unpack().0, [-1.0, -1.0, 0.0, 0.0]);
+
+    for bits in [0x00000000, 0x7f7f7f7f, 0x12345678, 0x81ff7f01] {
+        assert_eq!(Snorm8x4::pack(Snorm8x4(bits).unpack()).0, bits);
+        assert_eq!(Snorm16x2::pack(Snorm16x2(bits).unpack()).0, bits);
+    }
+}
+
+#[test]
+fn unorm10_10_10_2() {
+    assert_eq!(
+        Unorm10_10_10_2::pack(vec4(1.0, 0.0, 1.0, 1.0)).0,
+        0xc00003ff | (0x3ff << 20)
+    );
+    assert_eq!(
+        Unorm10_10_10_2::pack(vec4(0.0, 0.0, 0.0, 0.4)).0,
+        0x40000000
+    );
+}
+
+/// Decodes octahedral normal with the same formula as generated shader code.
+fn decode_oct(normal: OctNormal) -> [f32; 3] {
+    let [x, y] = Snorm16x2(normal.0).unpack().0;
+    let t = (x.abs() + y.abs() - 1.0).max(0.0);
+    let v = [
+        x - x.signum() * t,
+        y - y.signum() * t,
+        1.0 - x.abs() - y.abs(),
+    ];
+    let len = v.iter().map(|c| c * c).sum::<f32>().sqrt();
+    v.map(|c| c / len)
+}
+
+#[test]
+fn oct_normal() {
+    assert_eq!(OctNormal::pack(vec3(0.0, 0.0, 1.0)).0, 0);
+    assert_eq!(OctNormal::pack(vec3(0.0, 0.0, -1.0)).0, 0x7fff7fff);
+    assert_eq!(OctNormal::pack(vec3(1.0, 0.0, 0.0)).0, 0x7fff);
+
+    for normal in [
+        [1.0, 2.0, 3.0],
+        [-1.0, 0.5, -0.25],
+        [0.3, -0.9, -0.1],
+        [-0.7, -0.7, 0.1],
+        [0.0, 1.0, -1.0],
+    ] {
+        let len = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+        let decoded = decode_oct(OctNormal::pack(vec(normal)));
+        for (c, d) in normal.iter().zip(decoded) {
+            assert!((c / len - d).abs() < 1e-4, "{normal:?} {decoded:?}");
+        }
+    }
+}
+
+#[test]
+fn rgb9e5() {
+    assert_eq!(
+        Rgb9e5::pack(vec3(1.0, 0.5, 0.0)).0,
+        256 | (128 << 9) | (16 << 27)
+    );
+    assert_eq!(
+        Rgb9e5::pack(vec3(1.0, 0.5, 0.0)).unpack().0,
+        [1.0, 0.5, 0.0]
+    );
+
+    // Rounding of the largest component carries into the exponent.
+    assert_eq!(Rgb9e5::pack(vec3(0.9995, 0.0, 0.0)).0, 256 | (16 << 27));
+
+    // Out of range values are clamped, NaN becomes zero.
+    let clamped = Rgb9e5::pack(vec3(-1.0, f32::NAN, f32::INFINITY));
+    assert_eq!(clamped.unpack().0, [0.0, 0.0, Rgb9e5::MAX]);
+
+    // Error is within the step of the largest component, but not below the smallest step.
+    for value in [[0.1, 0.2, 0.3], [100.0, 0.001, 7.5], [1.0e-6, 0.0, 2.0e-6]] {
+        let unpacked = Rgb9e5::pack(vec(value)).unpack().0;
+        let max = value.iter().copied().fold(0.0, f32::max);
+        for (c, u) in value.iter().zip(unpacked) {
+            assert!(
+                (c - u).abs() <= (max / 256.0).max(2.0f32.powi(-24)),
+                "{value:?} {unpacked:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rg11b10f() {
+    assert_eq!(
+        Rg11b10f::pack(vec3(1.0, 2.0, 0.5)).0,
+        0x3c0 | (0x400 << 11) | (0x1c0 << 22)
+    );
+
+    // Negative values become zero, too large values become the largest finite value.
+    assert_eq!(
+        Rg11b10f::pack(vec3(-1.0, 1.0e6, f32::INFINITY)).0,
+        (0x7bf << 11) | (0x3e0 << 22)
+    );
+    assert!(Rg11b10f::pack(vec3(f32::NAN, 0.0, 0.0)).unpack().0[0].is_nan());
+
+    // Every finite value and infinity survive round trip.
+    for bits in 0..=0x7c0 {
+        let packed = Rg11b10f(bits | (bits << 11) | ((bits >> 1) << 22));
+        assert_eq!(Rg11b10f::pack(packed.unpack()), packed, "{bits:#x}");
+    }
+}
+
+#[derive(Clone, Copy, ShaderRepr)]
+struct Vertex {
+    color: Unorm8x4,
+    normal: Snorm16x2,
+    tangent: OctNormal,
+    emission: Rgb9e5,
+    irradiance: Rg11b10f,
+}
+
+#[test]
+fn codegen() {
+    assert_eq!(
+        <Vertex as CodeGen<Glsl>>::definition().unwrap(),
+        "struct Vertex {\n    uint color;\n    uint normal;\n    uint tangent;\n    uint emission;\n    uint irradiance;\n};\n\
+         vec4 Vertex_color(Vertex value) {\n    return Unorm8x4_unpack(value.color);\n}\n\
+         vec2 Vertex_normal(Vertex value) {\n    return Snorm16x2_unpack(value.normal);\n}\n\
+         vec3 Vertex_tangent(Vertex value) {\n    return OctNormal_unpack(value.tangent);\n}\n\
+         vec3 Vertex_emission(Vertex value) {\n    return Rgb9e5_unpack(value.emission);\n}\n\
+         vec3 Vertex_irradiance(Vertex value) {\n    return Rg11b10f_unpack(value.irradiance);\n}\n",
+    );
+    assert_eq!(
+        <Unorm8x4 as CodeGen<Glsl>>::definition().unwrap(),
+        "vec4 Unorm8x4_unpack(uint value) {\n    return unpackUnorm4x8(value);\n}\n",
+    );
+    assert_eq!(
+        <Snorm16x2 as CodeGen<Wgsl>>::definition().unwrap(),
+        "fn Snorm16x2_unpack(value: u32) -> vec2<f32> {\n    return unpack2x16snorm(value);\n}\n",
+    );
+    assert_eq!(
+        <Unorm10_10_10_2 as CodeGen<Msl>>::definition().unwrap(),
+        "float4 Unorm10_10_10_2_unpack(uint value) {\n    return unpack_unorm10a2_to_float(value);\n}\n",
+    );
+    assert!(<Unorm8x4 as CodeGen<Hlsl>>::definition()
+        .unwrap()
+        .contains("float4((value >> uint4(0, 8, 16, 24)) & 255) / 255.0"));
+
+    let offsets = <Vertex as ShaderFields<Wgsl>>::FIELDS
+        .iter()
+        .map(|field| (field.offset, field.size))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [(0, 4), (4, 4), (8, 4), (12, 4), (16, 4)]);
+
+    assert_eq!(
+        <OctNormal as CodeGen<Glsl>>::definition().unwrap(),
+        "vec3 OctNormal_unfold(vec2 p) {\n    return normalize(vec3(p - sign(p) * max(abs(p.x) + abs(p.y) - 1.0, 0.0), 1.0 - abs(p.x) - abs(p.y)));\n}\n\
+         vec3 OctNormal_unpack(uint value) {\n    return OctNormal_unfold(unpackSnorm2x16(value));\n}\n",
+    );
+    assert!(<Rg11b10f as CodeGen<Hlsl>>::definition()
+        .unwrap()
+        .starts_with("float3 Rg11b10f_unpack(uint value) {\n    return f16tof32("));
+    assert!(<Rgb9e5 as CodeGen<Wgsl>>::definition()
+        .unwrap()
+        .starts_with("fn Rgb9e5_unpack(value: u32) -> vec3<f32> {"));
+    assert!(<OctNormal as CodeGen<Msl>>::definition()
+        .unwrap()
+        .contains("float3 OctNormal_unfold(float2 p)"));
+}